@@ -0,0 +1,62 @@
+// Differential testing of the fast solvers against slow reference implementations.
+// Tests run a quick pass by default, the `_sweep` variants with more seeds and larger
+// cases are ignored and run with `cargo test -- --ignored`.
+use std::{fmt::Debug, panic};
+
+use crate::generate::Rng;
+
+const MAX_SHRINKS: usize = 1000;
+
+// runs `fast` and `reference` on cases made by `gen` for every seed; on a mismatch
+// (or a panic) the case is shrunk as long as it keeps failing and reported
+pub fn differential<C, T>(
+    name: &str,
+    seeds: impl IntoIterator<Item = u64>,
+    gen: impl Fn(&mut Rng) -> Option<C>,
+    shrink: impl Fn(&C) -> Vec<C>,
+    fast: impl Fn(&C) -> T,
+    reference: impl Fn(&C) -> T,
+) where
+    C: Debug,
+    T: Debug + PartialEq,
+{
+    let fails = |case: &C| -> Option<String> {
+        let expected = reference(case);
+        match panic::catch_unwind(panic::AssertUnwindSafe(|| fast(case))) {
+            Ok(actual) if actual == expected => None,
+            Ok(actual) => Some(format!("expected {:?}, got {:?}", expected, actual)),
+            Err(_) => Some(format!("expected {:?}, but panicked", expected)),
+        }
+    };
+    for seed in seeds {
+        let case = match gen(&mut Rng::new(seed)) {
+            None => continue, // not a valid case for this seed
+            Some(case) => case,
+        };
+        if let Some(mut failure) = fails(&case) {
+            let mut case = case;
+            let mut shrinks = 0;
+            'shrink: while shrinks < MAX_SHRINKS {
+                for smaller in shrink(&case) {
+                    if let Some(f) = fails(&smaller) {
+                        (case, failure) = (smaller, f);
+                        shrinks += 1;
+                        continue 'shrink;
+                    }
+                }
+                break;
+            }
+            panic!(
+                "{}: seed {} fails after {} shrinks: {}\n{:#?}",
+                name, seed, shrinks, failure, case
+            );
+        }
+    }
+}
+
+// candidates with one element removed
+pub fn remove_one<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    (0..items.len())
+        .map(|i| [&items[..i], &items[i + 1..]].concat())
+        .collect()
+}
@@ -0,0 +1,135 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+// how many search steps to take between deadline/cancel checks
+pub const CHECK_INTERVAL: u64 = 1 << 12;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    pub scanned: u64,
+    pub best: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome<T> {
+    Complete(T),
+    // stopped early, with the best result found so far (if any)
+    Interrupted(Option<T>),
+}
+
+impl<T> Outcome<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Outcome<U> {
+        match self {
+            Outcome::Complete(v) => Outcome::Complete(f(v)),
+            Outcome::Interrupted(v) => Outcome::Interrupted(v.map(f)),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Outcome::Complete(_))
+    }
+
+    pub fn value(self) -> Option<T> {
+        match self {
+            Outcome::Complete(v) => Some(v),
+            Outcome::Interrupted(v) => v,
+        }
+    }
+
+    pub fn unwrap(self) -> T {
+        match self {
+            Outcome::Complete(v) => v,
+            Outcome::Interrupted(_) => panic!("search was interrupted"),
+        }
+    }
+}
+
+type ProgressFn<'a> = Box<dyn Fn(&Progress) + Send + Sync + 'a>;
+
+#[derive(Default)]
+pub struct Context<'a> {
+    deadline: Option<Instant>,
+    cancel: Option<Arc<AtomicBool>>,
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn with_progress(mut self, f: impl Fn(&Progress) + Send + Sync + 'a) -> Self {
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|c| c.load(Ordering::Relaxed))
+            || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    pub fn report(&self, scanned: u64, best: Option<u64>) {
+        if let Some(f) = &self.progress {
+            f(&Progress { scanned, best });
+        }
+    }
+
+    // to be called every search step: reports progress and checks for cancellation
+    // every `CHECK_INTERVAL` steps
+    pub fn tick(&self, scanned: u64, best: Option<u64>) -> bool {
+        if scanned.is_multiple_of(CHECK_INTERVAL) {
+            self.report(scanned, best);
+            self.is_cancelled()
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_context() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let reports = Mutex::new(Vec::new());
+        let ctx = Context::new()
+            .with_cancel(cancel.clone())
+            .with_progress(|p| reports.lock().unwrap().push(p.scanned));
+        assert!(!ctx.tick(CHECK_INTERVAL, Some(1)));
+        cancel.store(true, Ordering::Relaxed);
+        assert!(!ctx.tick(CHECK_INTERVAL + 1, Some(1)));
+        assert!(ctx.tick(2 * CHECK_INTERVAL, Some(1)));
+        drop(ctx);
+        assert_eq!(
+            reports.into_inner().unwrap(),
+            vec![CHECK_INTERVAL, 2 * CHECK_INTERVAL]
+        );
+
+        let ctx = Context::new().with_timeout(Duration::ZERO);
+        assert!(ctx.is_cancelled());
+    }
+}
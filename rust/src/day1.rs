@@ -1,4 +1,4 @@
-use std::collections::BinaryHeap;
+use std::{cmp::Reverse, collections::BinaryHeap};
 
 use crate::util::load;
 
@@ -7,35 +7,120 @@ pub fn input() -> Vec<String> {
     calories
 }
 
-fn sorted_sums(calories: Vec<String>) -> Vec<u32> {
-    let (mut sums, last) = calories.iter().fold(
-        (BinaryHeap::new(), 0),
-        |(mut sums, current), cal| match cal.as_str() {
-            "" => {
-                sums.push(current);
-                (sums, 0)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Elf {
+    pub items: Vec<u32>,
+}
+
+impl Elf {
+    pub fn total(&self) -> u32 {
+        self.items.iter().sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub elves: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub empty: usize, // elves not carrying anything
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ledger {
+    pub elves: Vec<Elf>,
+}
+
+impl From<Vec<String>> for Ledger {
+    // elves are separated by blank lines, so two blank lines in a row make an empty elf
+    fn from(calories: Vec<String>) -> Self {
+        let mut elves = vec![Elf::default()];
+        for cal in calories {
+            match cal.as_str() {
+                "" => elves.push(Elf::default()),
+                s => elves.last_mut().unwrap().items.push(s.parse().unwrap()),
+            }
+        }
+        Ledger { elves }
+    }
+}
+
+impl Ledger {
+    // (index, total) of the `k` elves carrying the most, most first; ties go to
+    // the first elf
+    pub fn top(&self, k: usize) -> Vec<(usize, u32)> {
+        // min-heap of the best `k` so far
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        for (i, elf) in self.elves.iter().enumerate() {
+            best.push(Reverse((elf.total(), Reverse(i))));
+            if best.len() > k {
+                best.pop();
             }
-            s => (sums, current + s.parse::<u32>().unwrap()),
-        },
-    );
-    sums.push(last);
-    let mut v = sums.into_sorted_vec();
-    v.reverse();
-    v
+        }
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((total, Reverse(i)))| (i, total))
+            .collect()
+    }
+
+    // none without any elves, which have no mean or median
+    pub fn stats(&self) -> Option<Stats> {
+        if self.elves.is_empty() {
+            return None;
+        }
+        let mut totals: Vec<u32> = self.elves.iter().map(|e| e.total()).collect();
+        totals.sort_unstable();
+        let n = totals.len();
+        let mean = totals.iter().map(|&t| t as f64).sum::<f64>() / n as f64;
+        let median = if n % 2 == 1 {
+            totals[n / 2] as f64
+        } else {
+            (totals[n / 2 - 1] as f64 + totals[n / 2] as f64) / 2.
+        };
+        Some(Stats {
+            elves: n,
+            mean,
+            median,
+            empty: self.elves.iter().filter(|e| e.items.is_empty()).count(),
+        })
+    }
 }
 
 pub fn part1(calories: Vec<String>) -> u32 {
-    sorted_sums(calories)[0]
+    Ledger::from(calories).top(1)[0].1
 }
 
 pub fn part2(calories: Vec<String>) -> u32 {
-    sorted_sums(calories)[0..3].iter().sum()
+    Ledger::from(calories).top(3).iter().map(|(_, t)| t).sum()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_ledger() {
+        let calories = ["1000", "2000", "", "", "4000", "", "3000", "3000"];
+        let ledger = Ledger::from(calories.map(|s| s.to_owned()).to_vec());
+        assert_eq!(ledger.elves.len(), 4);
+        assert_eq!(ledger.top(2), vec![(3, 6000), (2, 4000)]);
+        assert_eq!(ledger.top(0), vec![]);
+        assert_eq!(ledger.top(10).len(), 4);
+        assert_eq!(
+            ledger.stats(),
+            Some(Stats {
+                elves: 4,
+                mean: 3250.,
+                median: 3500.,
+                empty: 1,
+            })
+        );
+
+        let empty = Ledger { elves: vec![] };
+        assert_eq!(empty.top(3), vec![]);
+        assert_eq!(empty.stats(), None);
+    }
+
     #[test]
     fn test_part1() {
         assert_eq!(part1(input()), 72017);
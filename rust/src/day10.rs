@@ -1,81 +1,784 @@
-use std::fmt::{Display, Formatter, Result};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter, Result},
+};
 
 use crate::util::load;
 
-#[derive(Clone, Copy)]
-struct ComSys {
-    cycle: usize,
-    sprite: i32,
-    crt: [char; 240],
+// what the extra instructions of an `Isa` do once their cycles are spent
+pub type Exec = fn(x: &mut i32, args: &[i32]);
+
+pub struct Op {
+    pub name: &'static str,
+    pub args: usize,
+    pub cycles: usize,
+    pub exec: Exec,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instr {
+    Noop,
+    Addx(i32),
+    Ext { op: usize, args: Vec<i32> }, // index into the ops of the `Isa`
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub line: usize,
+    pub text: String,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "line {}: cannot decode {:?}", self.line, self.text)
+    }
+}
+
+// the instruction set, with what every instruction costs; `noop` and `addx` come first
+pub struct Isa {
+    ops: Vec<Op>,
+}
+
+impl Default for Isa {
+    fn default() -> Self {
+        Isa {
+            ops: vec![
+                Op {
+                    name: "noop",
+                    args: 0,
+                    cycles: 1,
+                    exec: |_, _| (),
+                },
+                Op {
+                    name: "addx",
+                    args: 1,
+                    cycles: 2,
+                    exec: |x, args| *x += args[0],
+                },
+            ],
+        }
+    }
+}
+
+impl Isa {
+    pub fn add(&mut self, op: Op) {
+        self.ops.push(op);
+    }
+
+    pub fn set_cycles(&mut self, name: &str, cycles: usize) {
+        if let Some(op) = self.ops.iter_mut().find(|op| op.name == name) {
+            op.cycles = cycles;
+        }
+    }
+
+    fn op(&self, instr: &Instr) -> usize {
+        match instr {
+            Instr::Noop => 0,
+            Instr::Addx(_) => 1,
+            Instr::Ext { op, .. } => *op,
+        }
+    }
+
+    pub fn cycles(&self, instr: &Instr) -> usize {
+        self.ops[self.op(instr)].cycles
+    }
+
+    pub fn show(&self, instr: &Instr) -> String {
+        let args = match instr {
+            Instr::Noop => vec![],
+            Instr::Addx(v) => vec![*v],
+            Instr::Ext { args, .. } => args.clone(),
+        };
+        let mut s = self.ops[self.op(instr)].name.to_owned();
+        for a in args {
+            s += &format!(" {}", a);
+        }
+        s
+    }
+
+    // one instruction, counting lines from 1
+    pub fn decode(&self, line: usize, text: &str) -> std::result::Result<Instr, DecodeError> {
+        let err = || DecodeError {
+            line,
+            text: text.to_owned(),
+        };
+        let mut tokens = text.split_whitespace();
+        let name = tokens.next().ok_or_else(err)?;
+        let op = self
+            .ops
+            .iter()
+            .position(|op| op.name == name)
+            .ok_or_else(err)?;
+        let args: Vec<i32> = tokens
+            .map(|t| t.parse())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| err())?;
+        if args.len() != self.ops[op].args {
+            return Err(err());
+        }
+        Ok(match op {
+            0 => Instr::Noop,
+            1 => Instr::Addx(args[0]),
+            op => Instr::Ext { op, args },
+        })
+    }
+
+    pub fn decode_all<S: AsRef<str>>(
+        &self,
+        lines: &[S],
+    ) -> std::result::Result<Vec<Instr>, DecodeError> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, l)| self.decode(i + 1, l.as_ref()))
+            .collect()
+    }
+}
+
+// gets to see the register during every cycle, counting from 1
+pub trait Observer {
+    fn tick(&mut self, cycle: usize, x: i32);
+}
+
+// records every tick
+impl Observer for Vec<(usize, i32)> {
+    fn tick(&mut self, cycle: usize, x: i32) {
+        self.push((cycle, x));
+    }
+}
+
+// adds up cycle times register at cycles `first`, `first + every`, ...
+pub struct Signal {
+    pub first: usize,
+    pub every: usize,
+    pub sum: i32,
+}
+
+impl Default for Signal {
+    fn default() -> Self {
+        Signal {
+            first: 20,
+            every: 40,
+            sum: 0,
+        }
+    }
+}
+
+impl Observer for Signal {
+    fn tick(&mut self, cycle: usize, x: i32) {
+        if cycle >= self.first && (cycle - self.first).is_multiple_of(self.every) {
+            self.sum += cycle as i32 * x;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Ascii,  // `#` and `.`
+    Blocks, // full blocks on blank
+}
+
+// draws a pixel a cycle, row by row, lit where the sprite is; a screen that is full is kept
+// in `frames`, the last `max_frames` of them, and the next one starts out dark
+pub struct Crt {
+    width: usize,
+    height: usize,
+    sprite: usize, // pixels wide, around the register; nothing shows with 0
+    pub pixels: Vec<bool>,
+    pub frames: VecDeque<Vec<bool>>,
+    pub max_frames: usize,
+    drawn: usize, // cycles so far
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Crt::new(40, 6, 3)
+    }
+}
+
+impl Crt {
+    pub fn new(width: usize, height: usize, sprite: usize) -> Self {
+        assert!(width > 0 && height > 0, "empty {}x{} screen", width, height);
+        Crt {
+            width,
+            height,
+            sprite,
+            pixels: vec![false; width * height],
+            frames: VecDeque::new(),
+            max_frames: 16,
+            drawn: 0,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn sprite(&self) -> usize {
+        self.sprite
+    }
+
+    pub fn render_frame(&self, frame: &[bool], style: Style) -> String {
+        let (on, off) = match style {
+            Style::Ascii => ('#', '.'),
+            Style::Blocks => ('\u{2588}', ' '),
+        };
+        frame
+            .chunks(self.width)
+            .map(|row| {
+                let mut line: String = row.iter().map(|&p| if p { on } else { off }).collect();
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+
+    pub fn render(&self, style: Style) -> String {
+        self.render_frame(&self.pixels, style)
+    }
+
+    // the screen as last drawn in full: `pixels` if the last cycle finished it, else the
+    // newest of `frames`, and the part drawn so far if there is none
+    pub fn last_frame(&self) -> &[bool] {
+        match self.frames.back() {
+            Some(frame) if !self.drawn.is_multiple_of(self.pixels.len()) => frame,
+            _ => &self.pixels,
+        }
+    }
+}
+
+impl Observer for Crt {
+    fn tick(&mut self, cycle: usize, x: i32) {
+        self.drawn = cycle;
+        let pos = (cycle - 1) % self.pixels.len();
+        if pos == 0 && cycle > 1 {
+            let full = std::mem::replace(&mut self.pixels, vec![false; self.width * self.height]);
+            self.frames.push_back(full);
+            while self.frames.len() > self.max_frames {
+                self.frames.pop_front();
+            }
+        }
+        // the sprite covers `sprite` pixels from `x - left`
+        let left = self.sprite.saturating_sub(1) as i64 / 2;
+        let (col, from) = ((pos % self.width) as i64, x as i64 - left);
+        self.pixels[pos] = from <= col && col < from + self.sprite as i64;
+    }
+}
+
+impl Display for Crt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.render(Style::Ascii))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    Cycle(usize), // before the cycle starts
+    X(i32),       // after an instruction changes the register to this
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    Break(Breakpoint),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Trace {
+    pub pc: usize,
+    pub instr: String,
+    pub cycles: (usize, usize), // first and last
+    pub x: (i32, i32),          // before and after
+}
+
+pub struct Cpu {
+    pub isa: Isa,
+    pub x: i32,
+    pub cycle: usize, // cycles done
+    pub pc: usize,
+    spent: usize, // cycles into the current instruction
+    pub breakpoints: Vec<Breakpoint>,
+    pub trace: Option<Vec<Trace>>,
+    resuming: bool, // from a cycle breakpoint, which is not to fire again
 }
 
-impl ComSys {
-    fn new() -> Self {
-        Self {
+impl Cpu {
+    pub fn new(isa: Isa) -> Self {
+        Cpu {
+            isa,
+            x: 1,
             cycle: 0,
-            sprite: 1,
-            crt: ['.'; 6 * 40],
+            pc: 0,
+            spent: 0,
+            breakpoints: vec![],
+            trace: None,
+            resuming: false,
         }
     }
 
-    fn update_crt(&mut self) {
-        let is_visible = ((self.cycle as i32) % 40 - self.sprite).abs() < 2;
-        self.crt[self.cycle % 240] = if is_visible { '#' } else { '.' };
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
     }
 
-    fn process(&mut self, cmd: &str) {
-        let tokens: Vec<&str> = cmd.split(' ').collect();
-        self.update_crt();
-        match tokens[0] {
-            "noop" => self.cycle += 1,
-            "addx" => {
-                self.cycle += 1;
-                self.update_crt();
-                self.sprite += tokens[1].parse::<i32>().unwrap();
-                self.cycle += 1;
+    // runs until the program ends or a breakpoint is hit; running again carries on
+    pub fn run(&mut self, program: &[Instr], observers: &mut [&mut dyn Observer]) -> Stop {
+        while let Some(instr) = program.get(self.pc) {
+            let cycles = self.isa.cycles(instr);
+            while self.spent < cycles {
+                let cycle = self.cycle + 1;
+                let bp = Breakpoint::Cycle(cycle);
+                if !std::mem::take(&mut self.resuming) && self.breakpoints.contains(&bp) {
+                    self.resuming = true;
+                    return Stop::Break(bp);
+                }
+                for o in observers.iter_mut() {
+                    o.tick(cycle, self.x);
+                }
+                self.cycle = cycle;
+                self.spent += 1;
+            }
+            let before = self.x;
+            match instr {
+                Instr::Noop => (),
+                Instr::Addx(v) => self.x += v,
+                Instr::Ext { op, args } => (self.isa.ops[*op].exec)(&mut self.x, args),
+            }
+            if let Some(trace) = self.trace.as_mut() {
+                trace.push(Trace {
+                    pc: self.pc,
+                    instr: self.isa.show(instr),
+                    cycles: (self.cycle + 1 - cycles, self.cycle),
+                    x: (before, self.x),
+                });
+            }
+            self.pc += 1;
+            self.spent = 0;
+            let bp = Breakpoint::X(self.x);
+            if self.x != before && self.breakpoints.contains(&bp) {
+                return Stop::Break(bp);
+            }
+        }
+        Stop::Halted
+    }
+}
+
+// the letters of the 4x6 font, each followed by a blank column
+const FONT: &[(char, &str)] = &[
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Y', "#...#....#.#..#...#...#."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OcrError {
+    Size { width: usize, height: usize },
+    Unknown { pos: usize, glyph: String }, // glyph as rows of `#` and `.`
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Size { width, height } => write!(
+                f,
+                "a {}x{} screen is not a row of 4x6 letters",
+                width, height
+            ),
+            Self::Unknown { pos, glyph } => {
+                writeln!(f, "unknown letter at position {}:", pos)?;
+                write!(f, "{}", glyph)
             }
-            _ => panic!("unexpected command"),
         }
     }
 }
 
-impl Display for ComSys {
+// reads a screen of `#` and `.` rows, letters 5 columns apart
+pub fn ocr(screen: &str) -> std::result::Result<String, OcrError> {
+    let rows: Vec<&[u8]> = screen.lines().map(|l| l.as_bytes()).collect();
+    let width = rows.first().map_or(0, |r| r.len());
+    if rows.len() != 6 || rows.iter().any(|r| r.len() != width) || (width + 1) % 5 > 1 {
+        return Err(OcrError::Size {
+            width,
+            height: rows.len(),
+        });
+    }
+    (0..(width + 1) / 5)
+        .map(|pos| {
+            let glyph: String = rows
+                .iter()
+                .map(|r| String::from_utf8_lossy(&r[pos * 5..pos * 5 + 4]))
+                .collect();
+            FONT.iter()
+                .find(|(_, g)| *g == glyph)
+                .map(|&(c, _)| c)
+                .ok_or_else(|| OcrError::Unknown {
+                    pos,
+                    glyph: rows
+                        .iter()
+                        .map(|r| format!("{}\n", String::from_utf8_lossy(&r[pos * 5..pos * 5 + 4])))
+                        .collect(),
+                })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CompileError {
+    Size { width: usize, height: usize },
+    UnknownLetter(char),
+    TooLong(usize), // letters
+    // no program lights exactly the wanted pixels up to here
+    Unreachable { row: usize, col: usize },
+}
+
+impl Display for CompileError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        for line in self.crt.chunks(40) {
-            writeln!(f, "{}", line.iter().collect::<String>())?;
+        match self {
+            Self::Size { width, height } => {
+                write!(
+                    f,
+                    "a {}x{} image does not fit the 40x6 screen",
+                    width, height
+                )
+            }
+            Self::UnknownLetter(c) => write!(f, "no glyph for {:?}", c),
+            Self::TooLong(len) => write!(f, "{} letters do not fit on the screen", len),
+            Self::Unreachable { row, col } => {
+                write!(f, "the sprite cannot draw pixel {} of row {}", col, row)
+            }
+        }
+    }
+}
+
+// the screen showing `text` in the 4x6 font, from the left edge
+pub fn render(text: &str) -> std::result::Result<String, CompileError> {
+    let glyphs: Vec<&str> = text
+        .chars()
+        .map(|c| {
+            FONT.iter()
+                .find(|(l, _)| *l == c)
+                .map(|(_, g)| *g)
+                .ok_or(CompileError::UnknownLetter(c))
+        })
+        .collect::<std::result::Result<_, _>>()?;
+    if glyphs.len() > 8 {
+        return Err(CompileError::TooLong(glyphs.len()));
+    }
+    Ok((0..6)
+        .map(|row| {
+            let line: String = glyphs
+                .iter()
+                .map(|g| format!("{}.", &g[row * 4..row * 4 + 4]))
+                .collect();
+            format!("{:.<40}\n", line)
+        })
+        .collect())
+}
+
+// a program drawing `screen`, one cycle per pixel. The register can only change once it has
+// been the same for two cycles (the last two of the `addx` changing it), so this looks for
+// a run of register values one cycle at a time; every value below -1 or above 40 draws the
+// same nothing, so -2 and 41 stand in for them
+pub fn compile(screen: &str) -> std::result::Result<Vec<Instr>, CompileError> {
+    let rows: Vec<&[u8]> = screen.lines().map(|l| l.as_bytes()).collect();
+    if rows.len() != 6 || rows.iter().any(|r| r.len() != 40) {
+        return Err(CompileError::Size {
+            width: rows.first().map_or(0, |r| r.len()),
+            height: rows.len(),
+        });
+    }
+    let lit: Vec<bool> = rows.concat().into_iter().map(|b| b == b'#').collect();
+    const LOW: i32 = -2;
+    const VALUES: usize = 44;
+    // (value, run of 1 or at least 2 cycles) of every cycle, pointing to the state before
+    type State = [[Option<(usize, usize)>; 2]; VALUES];
+    let mut states: Vec<State> = vec![];
+    for (cycle, &lit) in lit.iter().enumerate() {
+        let shows = |v: usize| ((cycle % 40) as i32 - (v as i32 + LOW)).abs() <= 1;
+        let mut next = [[None; 2]; VALUES];
+        match states.last() {
+            None => next[(1 - LOW) as usize][0] = Some((0, 0)),
+            Some(prev) => {
+                for (v, runs) in prev.iter().enumerate() {
+                    for run in (0..2).filter(|&run| runs[run].is_some()) {
+                        next[v][1].get_or_insert((v, run));
+                        if run == 1 {
+                            for other in (0..VALUES).filter(|&o| o != v) {
+                                next[other][0].get_or_insert((v, run));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for (v, state) in next.iter_mut().enumerate() {
+            if shows(v) != lit {
+                *state = [None; 2];
+            }
         }
-        Ok(())
+        if next.iter().all(|s| s.iter().all(|s| s.is_none())) {
+            return Err(CompileError::Unreachable {
+                row: cycle / 40,
+                col: cycle % 40,
+            });
+        }
+        states.push(next);
+    }
+    // the register during every cycle, back to front; ending on a run of two or more where one
+    // fits, so the last two cycles can be an `addx` to whatever comes after
+    let (mut v, mut run) = [1, 0]
+        .into_iter()
+        .flat_map(|run| (0..VALUES).map(move |v| (v, run)))
+        .find(|&(v, run)| states[states.len() - 1][v][run].is_some())
+        .unwrap();
+    let mut xs = vec![];
+    for state in states.iter().rev() {
+        xs.push(v as i32 + LOW);
+        (v, run) = state[v][run].unwrap();
+    }
+    xs.reverse();
+    let mut program = vec![];
+    let mut start = 0;
+    for i in 1..=xs.len() {
+        if i < xs.len() && xs[i] == xs[start] {
+            continue;
+        }
+        if i < xs.len() {
+            program.extend(vec![Instr::Noop; i - start - 2]);
+            program.push(Instr::Addx(xs[i] - xs[start]));
+        } else {
+            program.extend(vec![Instr::Noop; i - start]);
+        }
+        start = i;
     }
+    Ok(program)
 }
 
-pub fn part1and2() -> i32 {
-    let lines: Vec<String> = load("data/day10.txt");
-    let mut sum = 0;
-    let mut signal_cycle = 20;
-    let mut cs = ComSys::new();
-    for line in lines {
-        let old = cs;
-        cs.process(&line);
-        if cs.cycle >= signal_cycle {
-            let signal = old.sprite
-                * (if cs.cycle == signal_cycle {
-                    cs.cycle
-                } else {
-                    cs.cycle - 1
-                }) as i32;
-            sum += signal;
-            signal_cycle += 40;
+pub fn compile_text(text: &str) -> std::result::Result<Vec<Instr>, CompileError> {
+    compile(&render(text)?)
+}
+
+// the program does not decode, or its screen does not read as text
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScreenError {
+    Decode(DecodeError),
+    Ocr(OcrError),
+}
+
+impl Display for ScreenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Decode(e) => write!(f, "{}", e),
+            Self::Ocr(e) => write!(f, "{}", e),
         }
     }
-    println!("{}", cs);
-    sum
 }
 
+fn run(filename: &str, observer: &mut dyn Observer) -> std::result::Result<(), DecodeError> {
+    let program = Isa::default().decode_all(&load::<String>(filename))?;
+    Cpu::new(Isa::default()).run(&program, &mut [observer]);
+    Ok(())
+}
+
+pub fn part1(filename: &str) -> std::result::Result<i32, DecodeError> {
+    let mut signal = Signal::default();
+    run(filename, &mut signal)?;
+    Ok(signal.sum)
+}
+
+pub fn part2(filename: &str) -> std::result::Result<String, ScreenError> {
+    let mut crt = Crt::default();
+    run(filename, &mut crt).map_err(ScreenError::Decode)?;
+    let screen = crt.render_frame(crt.last_frame(), Style::Ascii);
+    ocr(&screen).map_err(ScreenError::Ocr)
+}
+
+#[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ocr() {
+        let screen: Vec<String> = (0..6)
+            .map(|row| {
+                ["H", "I", "J"]
+                    .map(|l| {
+                        let (_, g) = FONT.iter().find(|(c, _)| c.to_string() == l).unwrap();
+                        g[row * 4..row * 4 + 4].to_owned()
+                    })
+                    .join(".")
+            })
+            .collect();
+        assert_eq!(ocr(&screen.join("\n")), Ok("HIJ".to_owned()));
+        let mut smudged = screen.clone();
+        smudged[0].replace_range(5..6, "#");
+        assert!(matches!(
+            ocr(&smudged.join("\n")),
+            Err(OcrError::Unknown { pos: 1, .. })
+        ));
+        assert_eq!(
+            ocr("##\n##"),
+            Err(OcrError::Size {
+                width: 2,
+                height: 2
+            })
+        );
+    }
+
     #[test]
-    fn test_part1and2() {
-        let sum = super::part1and2();
+    fn test_cpu() {
+        let mut isa = Isa::default();
+        isa.add(Op {
+            name: "mulx",
+            args: 1,
+            cycles: 3,
+            exec: |x, args| *x *= args[0],
+        });
+        isa.set_cycles("noop", 2);
+        let program = isa.decode_all(&["noop", "addx 3", "mulx -2"]).unwrap();
+        assert_eq!(
+            program[2],
+            Instr::Ext {
+                op: 2,
+                args: vec![-2]
+            }
+        );
+        assert_eq!(
+            isa.decode(4, "addx"),
+            Err(DecodeError {
+                line: 4,
+                text: "addx".to_owned()
+            })
+        );
+
+        let mut cpu = Cpu::new(isa).with_trace();
+        cpu.breakpoints = vec![Breakpoint::Cycle(2), Breakpoint::X(4)];
+        let mut ticks = vec![];
+        assert_eq!(
+            cpu.run(&program, &mut [&mut ticks]),
+            Stop::Break(Breakpoint::Cycle(2))
+        );
+        assert_eq!((cpu.cycle, cpu.pc), (1, 0));
+        assert_eq!(
+            cpu.run(&program, &mut [&mut ticks]),
+            Stop::Break(Breakpoint::X(4))
+        );
+        assert_eq!(cpu.run(&program, &mut [&mut ticks]), Stop::Halted);
+        assert_eq!(
+            ticks,
+            [(1, 1), (2, 1), (3, 1), (4, 1), (5, 4), (6, 4), (7, 4)]
+        );
+        assert_eq!(cpu.x, -8);
+        assert_eq!(
+            cpu.trace.unwrap()[2],
+            Trace {
+                pc: 2,
+                instr: "mulx -2".to_owned(),
+                cycles: (5, 7),
+                x: (4, -8)
+            }
+        );
+    }
+
+    #[test]
+    fn test_compile() {
+        let isa = Isa::default();
+        let program = compile_text("RGLRBZAU").unwrap();
+        assert!(program.ends_with(&[Instr::Noop, Instr::Noop]));
+        // through the source and back
+        let source: Vec<String> = program.iter().map(|i| isa.show(i)).collect();
+        let program = isa.decode_all(&source).unwrap();
+        let mut crt = Crt::default();
+        assert_eq!(Cpu::new(isa).run(&program, &mut [&mut crt]), Stop::Halted);
+        assert_eq!(crt.to_string(), render("RGLRBZAU").unwrap());
+        assert_eq!(ocr(&crt.to_string()), Ok("RGLRBZAU".to_owned()));
+
+        // the sprite starts over the first two pixels
+        assert_eq!(
+            compile_text("ABC"),
+            Err(CompileError::Unreachable { row: 0, col: 0 })
+        );
+        let mut screen = render("E").unwrap();
+        screen.replace_range(1..2, ".");
+        assert_eq!(
+            compile(&screen),
+            Err(CompileError::Unreachable { row: 0, col: 1 })
+        );
+        assert_eq!(compile_text("abc"), Err(CompileError::UnknownLetter('a')));
+    }
+
+    #[test]
+    fn test_crt() {
+        let mut crt = Crt::new(4, 2, 2);
+        for cycle in 1..=11 {
+            crt.tick(cycle, 1);
+        }
+        assert_eq!(crt.frames.len(), 1);
+        assert_eq!(
+            crt.render_frame(&crt.frames[0], Style::Ascii),
+            ".##.\n.##.\n"
+        );
+        assert_eq!(crt.last_frame(), &crt.frames[0][..]);
+        assert_eq!(crt.to_string(), ".##.\n....\n");
+        crt.tick(12, 3);
+        assert_eq!(
+            crt.render(Style::Blocks),
+            " \u{2588}\u{2588}\u{2588}\n    \n"
+        );
+        for cycle in 13..=16 {
+            crt.tick(cycle, 1);
+        }
+        assert_eq!(crt.frames.len(), 1);
+        assert_eq!(
+            crt.render_frame(crt.last_frame(), Style::Ascii),
+            ".###\n.##.\n"
+        );
+
+        let mut crt = Crt::new(2, 1, 0);
+        crt.max_frames = 2;
+        for cycle in 1..=7 {
+            crt.tick(cycle, 0);
+        }
+        assert_eq!(crt.frames.len(), 2);
+        assert!(crt
+            .frames
+            .iter()
+            .chain([&crt.pixels])
+            .flatten()
+            .all(|&p| !p));
+        assert!(std::panic::catch_unwind(|| Crt::new(0, 6, 3)).is_err());
+    }
+
+    #[test]
+    fn test_part1() {
+        let sum = part1("data/day10.txt").unwrap();
         println!("Signal strength sum: {}", sum);
         assert_eq!(sum, 14420);
     }
+
+    #[test]
+    fn test_part2() {
+        let text = part2("data/day10.txt").unwrap();
+        println!("CRT shows: {}", text);
+        assert_eq!(text, "RGLRBZAU");
+    }
 }
@@ -151,11 +151,11 @@ where
         }
     }
 }
-fn play<F>(num_rounds: u64, relief: F) -> u64
+fn play<F>(filename: &str, num_rounds: u64, relief: F) -> u64
 where
     F: Fn(u64) -> u64,
 {
-    let mut monkeys = parse_input("data/day11.txt");
+    let mut monkeys = parse_input(filename);
     for _ in 0..num_rounds {
         play_round(&mut monkeys, &relief);
     }
@@ -165,25 +165,25 @@ where
     counts[0] * counts[1]
 }
 
-pub fn part1() -> u64 {
-    play(20, |x| x / 3)
+pub fn part1(filename: &str) -> u64 {
+    play(filename, 20, |x| x / 3)
 }
 
-pub fn part2() -> u64 {
-    play(10000, |x| x)
+pub fn part2(filename: &str) -> u64 {
+    play(filename, 10000, |x| x)
 }
 
 mod tests {
     #[test]
     fn test_part1() {
-        let level = super::part1();
+        let level = super::part1("data/day11.txt");
         println!("Monkey level: {}", level);
         assert_eq!(level, 78678);
     }
 
     #[test]
     fn test_part2() {
-        let level = super::part2();
+        let level = super::part2("data/day11.txt");
         println!("Monkey level: {}", level);
         assert_eq!(level, 15333249714);
     }
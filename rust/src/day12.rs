@@ -180,15 +180,15 @@ impl Map {
     }
 }
 
-pub fn part1() -> usize {
-    let mut map = Map::load("data/day12.txt");
+pub fn part1(filename: &str) -> usize {
+    let mut map = Map::load(filename);
     let start = map.start;
     let top = map.top;
     map.grid.find_top(&start, &top)
 }
 
-pub fn part2() -> usize {
-    let mut map = Map::load("data/day12.txt");
+pub fn part2(filename: &str) -> usize {
+    let mut map = Map::load(filename);
     let top = map.top;
     let mut shortest = usize::MAX;
     for start in map.low_points.iter() {
@@ -201,14 +201,14 @@ pub fn part2() -> usize {
 mod tests {
     #[test]
     fn test_part1() {
-        let steps = super::part1();
+        let steps = super::part1("data/day12.txt");
         println!("Number of steps: {}", steps);
         assert_eq!(steps, 394);
     }
 
     #[test]
     fn test_part2() {
-        let steps = super::part2();
+        let steps = super::part2("data/day12.txt");
         println!("Number of steps: {}", steps);
         assert_eq!(steps, 388);
     }
@@ -92,8 +92,8 @@ impl PartialEq for Data {
 
 impl Eq for Data {}
 
-pub fn part1() -> usize {
-    let lines: Vec<String> = load("data/day13.txt");
+pub fn part1(filename: &str) -> usize {
+    let lines: Vec<String> = load(filename);
     let mut sum = 0;
     let mut index = 1;
     loop {
@@ -111,8 +111,8 @@ pub fn part1() -> usize {
     sum
 }
 
-pub fn part2() -> usize {
-    let mut packets: Vec<Data> = load::<String>("data/day13.txt")
+pub fn part2(filename: &str) -> usize {
+    let mut packets: Vec<Data> = load::<String>(filename)
         .into_iter()
         .filter(|l| !l.is_empty())
         .map(|l| Data::parse(&l))
@@ -172,14 +172,14 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let sum = super::part1();
+        let sum = super::part1("data/day13.txt");
         println!("Index-sum of right order pairs: {}", sum);
         assert_eq!(sum, 5625);
     }
 
     #[test]
     fn test_part2() {
-        let decoder_key = super::part2();
+        let decoder_key = super::part2("data/day13.txt");
         println!("Decoder key: {}", decoder_key);
         assert_eq!(decoder_key, 23111);
     }
@@ -46,8 +46,8 @@ struct Cave {
     units: usize,
 }
 
-fn load_cave(floor: bool) -> Cave {
-    let lines: Vec<String> = load("data/day14.txt");
+fn load_cave(filename: &str, floor: bool) -> Cave {
+    let lines: Vec<String> = load(filename);
     let mut grid = HashMap::new();
     let mut lowest = 0;
     for line in lines {
@@ -160,14 +160,14 @@ impl Cave {
         }
     }
 }
-pub fn part1() -> usize {
-    let mut cave = load_cave(false);
+pub fn part1(filename: &str) -> usize {
+    let mut cave = load_cave(filename, false);
     cave.sandfall(false);
     cave.units
 }
 
-pub fn part2() -> usize {
-    let mut cave = load_cave(true);
+pub fn part2(filename: &str) -> usize {
+    let mut cave = load_cave(filename, true);
     cave.sandfall(false);
     cave.units
 }
@@ -175,14 +175,14 @@ pub fn part2() -> usize {
 mod tests {
     #[test]
     fn test_part1() {
-        let units = super::part1();
+        let units = super::part1("data/day14.txt");
         println!("Units of sand a rest: {}", units);
         assert_eq!(units, 592);
     }
 
     #[test]
     fn test_part2() {
-        let units = super::part2();
+        let units = super::part2("data/day14.txt");
         println!("Units of sand a rest: {}", units);
         assert_eq!(units, 30367);
     }
@@ -1,8 +1,11 @@
 use std::str::FromStr;
 
-use crate::util::load;
+use crate::{
+    context::{Context, Outcome},
+    util::load,
+};
 
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct Coord {
     x: i32,
     y: i32,
@@ -21,7 +24,7 @@ impl Coord {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Sensor {
     loc: Coord,
     beacon: Coord,
@@ -50,8 +53,8 @@ impl Regions {
         let overlaps = self.list.drain_filter(|(x1,x2)|
         // full overlap
         (from <= *x1 && *x2 <= to) ||
-        // partial overlap
-        (*x1 <= from && from <= *x2) || (*x1 <= to && to <= *x2));
+        // partial overlap, or right next to each other
+        (*x1 <= from && from <= *x2 + 1) || (*x1 <= to + 1 && to <= *x2));
         let (mut nx1, mut nx2) = (from, to);
         for (x1, x2) in overlaps {
             nx1 = nx1.min(x1);
@@ -82,9 +85,23 @@ impl Regions {
     fn len(&self) -> i32 {
         self.list.iter().map(|(x1, x2)| x2 - x1 + 1).sum()
     }
+
+    // first position in `from..=to` not in any region
+    fn first_gap(&self, from: i32, to: i32) -> Option<i32> {
+        let mut list = self.list.clone();
+        list.sort();
+        let mut x = from;
+        for (x1, x2) in list {
+            if x1 > x {
+                break;
+            }
+            x = x.max(x2 + 1);
+        }
+        Some(x).filter(|x| *x <= to)
+    }
 }
 
-fn used_regions(sensors: &Vec<Sensor>, row: i32, ignore_beacon: bool) -> Regions {
+fn used_regions(sensors: &[Sensor], row: i32, ignore_beacon: bool) -> Regions {
     let mut regions = Regions { list: vec![] };
     for s in sensors {
         // add sensor sweep
@@ -95,50 +112,226 @@ fn used_regions(sensors: &Vec<Sensor>, row: i32, ignore_beacon: bool) -> Regions
                 regions.add(s.loc.x - d, s.loc.x + d);
             }
         }
-        // ignore beacon on row
-        if ignore_beacon && s.beacon.y == row {
+    }
+    // ignore beacons on row, once all sweeps are in
+    if ignore_beacon {
+        for s in sensors.iter().filter(|s| s.beacon.y == row) {
             regions.ignore(s.beacon.x);
         }
     }
     regions
 }
 
-pub fn part1() -> i32 {
+pub fn part1(filename: &str) -> i32 {
     let row = 2000000;
-    let sensors: Vec<Sensor> = load("data/day15.txt");
+    let sensors: Vec<Sensor> = load(filename);
     used_regions(&sensors, row, true).len()
 }
 
-pub fn part2() -> i64 {
-    let max = 4000000;
-    let sensors: Vec<Sensor> = load("data/day15.txt");
-    for y in 0..max {
-        let regions = used_regions(&sensors, y, false);
-        if regions.list.len() == 2 {
-            let (x11, x12) = regions.list[0];
-            let (x21, x22) = regions.list[1];
-            if x21 - x12 == 2 {
-                return max as i64 * (x12 as i64 + 1) + y as i64;
-            } else if x11 - x22 == 2 {
-                return max as i64 * (x22 as i64 + 1) + y as i64;
-            }
+fn tuning_frequency(sensors: &[Sensor], max: i32, ctx: &Context) -> Outcome<i64> {
+    for y in 0..=max {
+        if ctx.tick(y as u64, None) {
+            return Outcome::Interrupted(None);
+        }
+        if let Some(x) = used_regions(sensors, y, false).first_gap(0, max) {
+            return Outcome::Complete(max as i64 * x as i64 + y as i64);
         }
     }
     panic!("no result");
 }
 
+pub fn part2_with(filename: &str, ctx: &Context) -> Outcome<i64> {
+    let sensors: Vec<Sensor> = load(filename);
+    tuning_frequency(&sensors, 4000000, ctx)
+}
+
+pub fn part2(filename: &str) -> i64 {
+    part2_with(filename, &Context::new()).unwrap()
+}
+
+#[cfg(test)]
 mod tests {
+    use std::ops::Range;
+
+    use super::*;
+    use crate::{
+        check::{differential, remove_one},
+        generate::Rng,
+    };
+
+    fn covered(sensors: &[Sensor], c: &Coord) -> bool {
+        sensors.iter().any(|s| s.loc.dist(c) <= s.dist)
+    }
+
+    fn reference_row(sensors: &[Sensor], row: i32) -> i32 {
+        let (lo, hi) = (-100, 100);
+        (lo..=hi)
+            .map(|x| Coord { x, y: row })
+            .filter(|c| covered(sensors, c) && !sensors.iter().any(|s| s.beacon == *c))
+            .count() as i32
+    }
+
+    fn reference_frequency(sensors: &[Sensor], max: i32) -> Vec<i64> {
+        (0..=max)
+            .flat_map(|x| (0..=max).map(move |y| Coord { x, y }))
+            .filter(|c| !covered(sensors, c))
+            .map(|c| max as i64 * c.x as i64 + c.y as i64)
+            .collect()
+    }
+
+    // a sensor around `loc` that just does not reach `hidden`
+    fn sensor_near(rng: &mut Rng, loc: Coord, hidden: &Coord) -> Option<Sensor> {
+        let dist = loc.dist(hidden) - 1;
+        if dist < 1 {
+            return None;
+        }
+        let dx = rng.range(-dist as i64, dist as i64) as i32;
+        let dy = (dist - dx.abs()) * rng.pick(&[-1, 1]);
+        let beacon = Coord {
+            x: loc.x + dx,
+            y: loc.y + dy,
+        };
+        Some(Sensor { loc, beacon, dist })
+    }
+
+    // sensors covering all of `0..=max` squared but a single hidden spot
+    fn gen_sensors(rng: &mut Rng, max: i32) -> Option<Vec<Sensor>> {
+        let hidden = Coord {
+            x: rng.range(0, max as i64) as i32,
+            y: rng.range(0, max as i64) as i32,
+        };
+        let mut sensors = vec![];
+        for _ in 0..100 {
+            let loc = Coord {
+                x: rng.range(-2, max as i64 + 2) as i32,
+                y: rng.range(-2, max as i64 + 2) as i32,
+            };
+            sensors.extend(sensor_near(rng, loc, &hidden));
+            if reference_frequency(&sensors, max).len() == 1 {
+                return Some(sensors);
+            }
+        }
+        None
+    }
+
+    fn part1_differential(seeds: Range<u64>, max: i64) {
+        differential(
+            "day15 row",
+            seeds,
+            |rng| {
+                let max = rng.range(4, max) as i32;
+                let sensors = gen_sensors(rng, max)?;
+                Some((rng.range(-5, max as i64 + 5) as i32, sensors))
+            },
+            |(row, sensors)| {
+                remove_one(sensors)
+                    .into_iter()
+                    .map(|smaller| (*row, smaller))
+                    .collect()
+            },
+            |(row, sensors)| used_regions(sensors, *row, true).len(),
+            |(row, sensors)| reference_row(sensors, *row),
+        );
+    }
+
+    #[test]
+    fn test_part1_differential() {
+        part1_differential(0..100, 12);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_part1_sweep() {
+        part1_differential(0..300, 20);
+    }
+
+    fn part2_differential(seeds: Range<u64>, max: i64) {
+        differential(
+            "day15 tuning frequency",
+            seeds,
+            |rng| {
+                let max = rng.range(4, max) as i32;
+                Some((max, gen_sensors(rng, max)?))
+            },
+            |(max, sensors)| {
+                remove_one(sensors)
+                    .into_iter()
+                    .filter(|smaller| reference_frequency(smaller, *max).len() == 1)
+                    .map(|smaller| (*max, smaller))
+                    .collect()
+            },
+            |(max, sensors)| vec![tuning_frequency(sensors, *max, &Context::new()).unwrap()],
+            |(max, sensors)| reference_frequency(sensors, *max),
+        );
+    }
+
+    #[test]
+    fn test_part2_differential() {
+        part2_differential(0..100, 12);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_part2_sweep() {
+        part2_differential(0..300, 20);
+    }
+
+    fn sensors(lines: &[&str]) -> Vec<Sensor> {
+        lines.iter().map(|l| l.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_regions() {
+        // touching ranges are one region
+        let mut regions = Regions { list: vec![] };
+        regions.add(0, 4);
+        regions.add(5, 9);
+        assert_eq!(regions.list, [(0, 9)]);
+
+        // a beacon stays out when a later sensor sweeps over it again
+        let s = sensors(&[
+            "Sensor at x=0, y=0: closest beacon is at x=0, y=2",
+            "Sensor at x=0, y=5: closest beacon is at x=0, y=9",
+        ]);
+        assert_eq!(used_regions(&s, 2, true).len(), 2);
+    }
+
+    #[test]
+    fn test_tuning_frequency_edges() {
+        // the spot on the left edge, a single region on its row
+        let s = sensors(&[
+            "Sensor at x=4, y=0: closest beacon is at x=9, y=0",
+            "Sensor at x=4, y=4: closest beacon is at x=9, y=4",
+        ]);
+        assert_eq!(tuning_frequency(&s, 4, &Context::new()).unwrap(), 2);
+
+        // the spot on the last row
+        let s = sensors(&[
+            "Sensor at x=0, y=0: closest beacon is at x=5, y=0",
+            "Sensor at x=4, y=0: closest beacon is at x=9, y=0",
+        ]);
+        assert_eq!(tuning_frequency(&s, 4, &Context::new()).unwrap(), 4 * 2 + 4);
+    }
+
     #[test]
     fn test_part1() {
-        let num = super::part1();
+        let num = super::part1("data/day15.txt");
         println!("Number of positions not containing a beacon: {}", num);
         assert_eq!(num, 4748135);
     }
 
     #[test]
     fn test_part2() {
-        let freq = super::part2();
+        let freq = super::part2("data/day15.txt");
         println!("Tuning frequency: {}", freq);
         assert_eq!(freq, 13743542639657);
     }
+
+    #[test]
+    fn test_part2_interrupted() {
+        use crate::context::{Context, Outcome};
+        let ctx = Context::new().with_timeout(std::time::Duration::ZERO);
+        let freq = super::part2_with("data/day15.txt", &ctx);
+        assert_eq!(freq, Outcome::Interrupted(None));
+    }
 }
@@ -3,7 +3,10 @@ use std::{
     collections::{BinaryHeap, HashMap},
 };
 
-use crate::util::load;
+use crate::{
+    context::{Context, Outcome},
+    util::load,
+};
 
 #[derive(Debug)]
 struct QueueItem<'a> {
@@ -114,14 +117,58 @@ impl Rooms {
         self.distances = m;
     }
 
-    fn find_max_pressure<'a>(&'a self, q: &mut BinaryHeap<QueueItem<'a>>, num: usize) -> u32 {
+    // pressure if every closed valve were opened by whoever can get there first
+    fn upper_bound(&self, qi: &QueueItem) -> u32 {
+        let mut bound = qi.pressure;
+        for v in self.valves.iter() {
+            let other = &self.rooms[v];
+            if qi.visited & (1 << other.id) > 0 {
+                continue;
+            }
+            let best_tl = (0..2)
+                .filter(|&i| !qi.name[i].is_empty())
+                .map(|i| {
+                    let from = self.rooms[qi.name[i]].id;
+                    qi.time_left[i].saturating_sub(self.distances[from][other.id] + 1)
+                })
+                .max()
+                .unwrap_or(0);
+            bound += best_tl * other.rate;
+        }
+        bound
+    }
+
+    fn find_max_pressure<'a>(
+        &'a self,
+        q: &mut BinaryHeap<QueueItem<'a>>,
+        num: usize,
+        ctx: &Context,
+    ) -> Outcome<u32> {
         let mut max = 0;
+        let mut scanned = 0;
         loop {
+            scanned += 1;
+            if ctx.tick(scanned, Some(max as u64)) {
+                return Outcome::Interrupted(Some(max));
+            }
             match q.pop() {
-                None => return max,
+                None => return Outcome::Complete(max),
+                Some(qi) if self.upper_bound(&qi) <= max => continue,
                 Some(qi) => {
                     max = max.max(qi.pressure);
                     let current: &Room = &self.rooms[qi.name[qi.next]];
+                    // time left for the other actor, if any
+                    let other_tl = qi.time_left[(qi.next + 1) % 2];
+                    if other_tl > 2 {
+                        // this actor is done, the other one carries on alone
+                        let mut time_left = qi.time_left;
+                        time_left[qi.next] = 0;
+                        q.push(QueueItem {
+                            time_left,
+                            next: (qi.next + 1) % num,
+                            ..qi
+                        });
+                    }
                     for v in self.valves.iter() {
                         let other = &self.rooms[v];
                         if qi.visited & (1 << other.id) > 0 {
@@ -131,7 +178,7 @@ impl Rooms {
                         let mut tl = qi.time_left[qi.next];
                         if tl >= cost {
                             tl -= cost;
-                            if tl <= 2 {
+                            if tl <= 2 && other_tl <= 2 {
                                 // no more time to move and open a valve
                                 max = max.max(qi.pressure + tl * other.rate);
                             } else {
@@ -155,8 +202,8 @@ impl Rooms {
     }
 }
 
-pub fn part1() -> u32 {
-    let mut rooms = Rooms::load("data/day16.txt");
+pub fn part1_with(filename: &str, ctx: &Context) -> Outcome<u32> {
+    let mut rooms = Rooms::load(filename);
     rooms.init_distances();
     let mut queue = BinaryHeap::new();
     queue.push(QueueItem {
@@ -166,11 +213,15 @@ pub fn part1() -> u32 {
         pressure: 0,
         visited: 0,
     });
-    rooms.find_max_pressure(&mut queue, 1)
+    rooms.find_max_pressure(&mut queue, 1, ctx)
 }
 
-pub fn part2() -> u32 {
-    let mut rooms = Rooms::load("data/day16.txt");
+pub fn part1(filename: &str) -> u32 {
+    part1_with(filename, &Context::new()).unwrap()
+}
+
+pub fn part2_with(filename: &str, ctx: &Context) -> Outcome<u32> {
+    let mut rooms = Rooms::load(filename);
     rooms.init_distances();
     let mut queue = BinaryHeap::new();
     queue.push(QueueItem {
@@ -180,20 +231,157 @@ pub fn part2() -> u32 {
         pressure: 0,
         visited: 0,
     });
-    rooms.find_max_pressure(&mut queue, 2)
+    rooms.find_max_pressure(&mut queue, 2, ctx)
 }
 
+pub fn part2(filename: &str) -> u32 {
+    part2_with(filename, &Context::new()).unwrap()
+}
+
+#[cfg(test)]
 mod tests {
+    use std::ops::Range;
+
+    use super::*;
+    use crate::{check::differential, generate::generate, util::TempInput};
+
+    // best pressure for every set of opened valves, trying every order
+    fn reference_best(rooms: &Rooms, time: u32) -> HashMap<u64, u32> {
+        fn visit(
+            rooms: &Rooms,
+            at: usize,
+            time_left: u32,
+            visited: u64,
+            pressure: u32,
+            best: &mut HashMap<u64, u32>,
+        ) {
+            let b = best.entry(visited).or_default();
+            *b = (*b).max(pressure);
+            for v in &rooms.valves {
+                let other = &rooms.rooms[v];
+                let cost = rooms.distances[at][other.id] + 1;
+                if visited & (1 << other.id) == 0 && cost < time_left {
+                    let tl = time_left - cost;
+                    let visited = visited | (1 << other.id);
+                    visit(
+                        rooms,
+                        other.id,
+                        tl,
+                        visited,
+                        pressure + tl * other.rate,
+                        best,
+                    );
+                }
+            }
+        }
+        let mut best = HashMap::new();
+        visit(rooms, rooms.rooms["AA"].id, time, 0, 0, &mut best);
+        best
+    }
+
+    fn reference(lines: &[String]) -> (u32, u32) {
+        let input = TempInput::new(lines.concat()).unwrap();
+        let mut rooms = Rooms::load(input.filename());
+        rooms.init_distances();
+        let part1 = reference_best(&rooms, 30).into_values().max().unwrap();
+        let best = reference_best(&rooms, 26);
+        let part2 = best
+            .iter()
+            .flat_map(|(m1, p1)| {
+                best.iter()
+                    .filter(move |(m2, _)| *m1 & *m2 == 0)
+                    .map(move |(_, p2)| p1 + p2)
+            })
+            .max()
+            .unwrap();
+        (part1, part2)
+    }
+
+    fn fast(lines: &[String]) -> (u32, u32) {
+        let input = TempInput::new(lines.concat()).unwrap();
+        (part1(input.filename()), part2(input.filename()))
+    }
+
+    // candidates with one valve stuck at flow rate 0
+    fn close_one(lines: &[String]) -> Vec<Vec<String>> {
+        (0..lines.len())
+            .filter(|&i| !lines[i].contains("rate=0;"))
+            .map(|i| {
+                let mut lines = lines.to_vec();
+                let (head, tail) = lines[i].split_once("rate=").unwrap();
+                let tail = &tail[tail.find(';').unwrap()..];
+                lines[i] = format!("{}rate=0{}", head, tail);
+                lines
+            })
+            .collect()
+    }
+
+    fn valves_differential(seeds: Range<u64>, max_size: i64) {
+        differential(
+            "day16",
+            seeds,
+            |rng| {
+                let size = rng.range(2, max_size) as usize;
+                let input = generate(16, rng.next_u64(), size);
+                Some(input.split_inclusive('\n').map(|l| l.to_owned()).collect())
+            },
+            |lines: &Vec<String>| close_one(lines),
+            |lines| fast(lines),
+            |lines| reference(lines),
+        );
+    }
+
+    #[test]
+    fn test_differential() {
+        valves_differential(0..50, 10);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_sweep() {
+        valves_differential(0..200, 16);
+    }
+
+    // one actor can only open the far valve at the very end of its time, the other
+    // has to keep going and open both near ones
+    #[test]
+    fn test_far_valve() {
+        let corridor: Vec<String> = (0..22)
+            .map(|i| format!("C{}", (b'A' + i) as char))
+            .collect();
+        let mut input = format!(
+            "Valve AA has flow rate=0; tunnels lead to valves N1, N2, {}\n",
+            corridor[0]
+        );
+        input += "Valve N1 has flow rate=1; tunnel leads to valve AA\n";
+        input += "Valve N2 has flow rate=1; tunnel leads to valve AA\n";
+        for (i, room) in corridor.iter().enumerate() {
+            let back = if i == 0 { "AA" } else { &corridor[i - 1] };
+            let ahead = corridor.get(i + 1).map_or("FA", |r| r);
+            input += &format!(
+                "Valve {} has flow rate=0; tunnels lead to valves {}, {}\n",
+                room, back, ahead
+            );
+        }
+        input += &format!(
+            "Valve FA has flow rate=100; tunnel leads to valve {}\n",
+            corridor[21]
+        );
+        let input = TempInput::new(input).unwrap();
+        assert_eq!(part1(input.filename()), 600);
+        assert_eq!(part2(input.filename()), 2 * 100 + 24 + 21);
+    }
+
     #[test]
     fn test_part1() {
-        let pressure = super::part1();
+        let pressure = super::part1("data/day16.txt");
         println!("Pressure: {}", pressure);
         assert_eq!(pressure, 1789);
     }
 
     #[test]
     fn test_part2() {
-        let pressure = super::part2();
+        let pressure = super::part2("data/day16.txt");
         println!("Pressure: {}", pressure);
         assert_eq!(pressure, 2496);
     }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::util::load;
 
@@ -63,6 +63,10 @@ fn rocks() -> Vec<Rock> {
     ]
 }
 
+// deeper surfaces, like a shaft still open down to the floor, are not compared; that
+// only puts off finding the repeat, it can't make one up
+const MAX_SURFACE: usize = 64;
+
 struct Cave {
     jet: Vec<char>,
     rocks: HashSet<i64>,
@@ -73,7 +77,10 @@ struct Cave {
 
 impl Cave {
     fn load(filename: &str) -> Self {
-        let jet: Vec<char> = load::<String>(filename)[0].chars().collect();
+        Cave::new(load::<String>(filename)[0].chars().collect())
+    }
+
+    fn new(jet: Vec<char>) -> Self {
         Cave {
             jet,
             rocks: HashSet::new(),
@@ -116,6 +123,34 @@ impl Cave {
     //     }
     // }
 
+    // the empty cells a falling rock can still get to, one bit per column and row down
+    // from the top; rocks only move down and sideways, and whatever lies outside this
+    // surface is never looked at again. None when it goes deeper than `MAX_SURFACE`
+    fn surface(&self) -> Option<Vec<u8>> {
+        let top = self.max_y - 3;
+        let mut rows = vec![0u8];
+        let mut todo: Vec<(i64, i64)> = (0..7).map(|x| (x, top)).collect();
+        while let Some((x, y)) = todo.pop() {
+            let depth = (top - y) as usize;
+            if depth == MAX_SURFACE {
+                return None;
+            }
+            if depth == rows.len() {
+                rows.push(0);
+            }
+            if rows[depth] & 1 << x != 0 {
+                continue;
+            }
+            rows[depth] |= 1 << x;
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1)] {
+                if (0..7).contains(&nx) && ny >= 0 && !self.rocks.contains(&Self::coord(nx, ny)) {
+                    todo.push((nx, ny));
+                }
+            }
+        }
+        Some(rows)
+    }
+
     pub fn rock_fall(&mut self, num: usize) -> i64 {
         let rocks = rocks();
         for _ in 0..num {
@@ -153,98 +188,100 @@ impl Cave {
     }
 }
 
-pub fn part1() -> i64 {
-    let mut cave = Cave::load("data/day17.txt");
+pub fn part1(filename: &str) -> i64 {
+    let mut cave = Cave::load(filename);
     cave.rock_fall(2022)
 }
 
-pub fn part2() -> usize {
-    fn find_pps(deltas: &Vec<usize>, cycle: usize, delta: usize) -> (Option<usize>, usize) {
-        let mut pps = None;
-        let mut prs = 0;
-        let mut i: usize = cycle;
-        while i > 1 {
-            i -= 1;
-            if deltas[i] == delta {
-                pps = Some(i);
-                prs = cycle;
-                break;
-            }
-        }
-        (pps, prs)
-    }
-
-    let mut cave = Cave::load("data/day17.txt");
+// tower height after `rounds` times 5 rocks (one of each kind), extrapolated once
+// the cave repeats: same jet position and the same surface
+fn tower_height(mut cave: Cave, rounds: usize) -> usize {
     let mut deltas = Vec::new();
-    let mut prev = 0;
-    let times = 10; // expect at least this number of repeats
-    let mut pps = None; // possible pattern start
-    let mut prs = 0; // possible repeat start
-    let mut cycle = 0;
-    loop {
-        // let rocks fall in multiples of 5 (one of each kind)
-        cave.rock_fall(5);
-        let d = (cave.max_y - prev) as usize;
-        deltas.push(d);
-        match pps {
-            None => (pps, prs) = find_pps(&deltas, cycle, d),
-            Some(start) => {
-                let offset = cycle - prs;
-                if deltas[start + offset] != d {
-                    (pps, prs) = find_pps(&deltas, cycle, d);
-                }
-            }
-        }
-        // println!(
-        //     "{:2} delta max = {:2}, pps = {:?}, prs = {}",
-        //     cycle, d, pps, prs
-        // );
-        cycle += 1;
-        if let Some(s) = pps {
-            let len = prs - s; // pattern length
-            if cycle == s + (times + 1) * len {
-                break;
-            }
+    let mut seen = HashMap::new();
+    // the tower starts with the 3 empty rows above the floor
+    let mut prev = cave.max_y;
+    for cycle in 0..rounds {
+        let start = cave
+            .surface()
+            .and_then(|surface| seen.insert((cave.gas, surface), cycle));
+        if let Some(start) = start {
+            let len = cycle - start; // pattern length
+            let repeats = (rounds - start) / len;
+            let remainder = rounds - start - repeats * len;
+            return deltas[0..start].iter().sum::<usize>()
+                + repeats * deltas[start..cycle].iter().sum::<usize>()
+                + deltas[start..(start + remainder)].iter().sum::<usize>();
         }
+        cave.rock_fall(5);
+        deltas.push((cave.max_y - prev) as usize);
         prev = cave.max_y;
     }
-    let start = pps.unwrap();
-    println!(
-        "Found {} repeats of {}..{} : {:?}",
-        times,
-        start,
-        prs,
-        &deltas[start..prs]
-    );
-    let rocks = 1000000000000 / 5; // max is recorded per 5 rocks
-    let len = prs - start;
-    let repeats = (rocks - start) / len;
-    let remainder = rocks - start - repeats * len;
-    println!(
-        "Prefix = {}, repeats = {} * {} (with increase {}), suffix = {}",
-        start,
-        repeats,
-        len * 5,
-        deltas[start..prs].iter().sum::<usize>(),
-        remainder
-    );
-    deltas[0..start].iter().sum::<usize>()
-        + repeats * deltas[start..prs].iter().sum::<usize>()
-        + deltas[start..(start + remainder)].iter().sum::<usize>()
-        - 3 // why??
+    // all simulated, nothing to extrapolate
+    deltas.iter().sum()
+}
+
+pub fn part2(filename: &str) -> usize {
+    let rounds = 1000000000000 / 5; // max is recorded per 5 rocks
+    tower_height(Cave::load(filename), rounds)
 }
 
+#[cfg(test)]
 mod tests {
+    use std::ops::Range;
+
+    use super::*;
+    use crate::check::differential;
+
+    fn tower_height_differential(seeds: Range<u64>, max_rounds: i64) {
+        differential(
+            "day17",
+            seeds,
+            |rng| {
+                let len = rng.range(1, 50) as usize;
+                let jet: Vec<char> = (0..len).map(|_| *rng.pick(&['<', '>'])).collect();
+                Some((jet, rng.range(0, max_rounds) as usize))
+            },
+            |(jet, rounds)| {
+                let mut smaller = vec![];
+                if *rounds > 0 {
+                    smaller.push((jet.clone(), rounds / 2));
+                }
+                if jet.len() > 1 {
+                    smaller.extend((0..jet.len()).map(|i| {
+                        let mut jet = jet.clone();
+                        jet.remove(i);
+                        (jet, *rounds)
+                    }));
+                }
+                smaller
+            },
+            |(jet, rounds)| tower_height(Cave::new(jet.clone()), *rounds),
+            // dropping every single rock
+            |(jet, rounds)| Cave::new(jet.clone()).rock_fall(rounds * 5) as usize,
+        );
+    }
+
+    #[test]
+    fn test_tower_height_differential() {
+        tower_height_differential(0..50, 1000);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_tower_height_sweep() {
+        tower_height_differential(0..300, 5000);
+    }
+
     #[test]
     fn test_part1() {
-        let height = super::part1();
+        let height = super::part1("data/day17.txt");
         println!("Tower height: {}", height);
         assert_eq!(height, 3133);
     }
 
     #[test]
     fn test_part2() {
-        let height = super::part2();
+        let height = super::part2("data/day17.txt");
         println!("Tower height: {}", height);
         assert_eq!(height, 1547953216393);
     }
@@ -84,8 +84,8 @@ fn flood_fill(scan: &mut Scan, c: &Coord) {
     }
 }
 
-pub fn part1() -> u32 {
-    let scan = load_scan("data/day18.txt");
+pub fn part1(filename: &str) -> u32 {
+    let scan = load_scan(filename);
     let mut surface = 0;
     for (c, _) in &scan {
         let mut surf = 6;
@@ -99,8 +99,8 @@ pub fn part1() -> u32 {
     surface
 }
 
-pub fn part2() -> u32 {
-    let mut scan = load_scan("data/day18.txt");
+pub fn part2(filename: &str) -> u32 {
+    let mut scan = load_scan(filename);
     flood_fill(&mut scan, &Coord { x: 0, y: 0, z: 0 });
     let mut surface = 0;
     for (c, t) in &scan {
@@ -124,14 +124,14 @@ pub fn part2() -> u32 {
 mod tests {
     #[test]
     fn test_part1() {
-        let area = super::part1();
+        let area = super::part1("data/day18.txt");
         println!("Surface area: {}", area);
         assert_eq!(area, 4400);
     }
 
     #[test]
     fn test_part2() {
-        let area = super::part2();
+        let area = super::part2("data/day18.txt");
         println!("Exterior surface area: {}", area);
         assert_eq!(area, 2522);
     }
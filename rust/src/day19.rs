@@ -6,7 +6,10 @@ use std::{
     str::FromStr,
 };
 
-use crate::util::load;
+use crate::{
+    context::{Context, Outcome},
+    util::load,
+};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 enum Resource {
@@ -209,7 +212,7 @@ impl<'a> State<'a> {
 struct Blueprint {
     id: u32,
     rules: Vec<Robot>,
-    max: Counters, // maximum amount of resources needed to produce all robots once
+    max: Counters, // most of each resource any robot needs, no point producing more per minute
 }
 
 impl FromStr for Blueprint {
@@ -224,7 +227,7 @@ impl FromStr for Blueprint {
             .map(|r| r.parse().unwrap())
             .collect();
         let max = Counters {
-            ore: rules.iter().map(|r| r.needs.ore).sum(),
+            ore: rules.iter().map(|r| r.needs.ore).max().unwrap(),
             clay: rules[2].needs.clay,
             obsidian: rules[3].needs.obsidian,
             geode: 0,
@@ -234,7 +237,16 @@ impl FromStr for Blueprint {
 }
 
 impl Blueprint {
-    fn produce(&self, time: u32) -> u32 {
+    fn enough_robots(&self, robot: &Robot, robots: &Counters) -> bool {
+        match robot.generates {
+            Resource::Ore => robots.ore >= self.max.ore,
+            Resource::Clay => robots.clay >= self.max.clay,
+            Resource::Obsidian => robots.obsidian >= self.max.obsidian,
+            Resource::Geode => false,
+        }
+    }
+
+    fn produce(&self, time: u32, ctx: &Context) -> Outcome<u32> {
         // not ideal yet
         fn should_add(s: &State, best: u32) -> bool {
             // assuming we can make a geode robot for each `tl` left and generate geodes along the way....
@@ -243,13 +255,6 @@ impl Blueprint {
             {
                 return false;
             }
-            // more inventory than needed for creating robots... we should have created at least one
-            if (s.inv.ore > s.bp.max.ore)
-                && (s.inv.clay > s.bp.max.clay)
-                && (s.inv.obsidian > s.bp.max.obsidian)
-            {
-                return false;
-            }
             // all ok
             true
         }
@@ -266,7 +271,12 @@ impl Blueprint {
         }]);
         let mut visited = HashSet::new();
         let mut max = 0;
+        let mut scanned = 0;
         loop {
+            scanned += 1;
+            if ctx.tick(scanned, Some(max as u64)) {
+                return Outcome::Interrupted(Some(max));
+            }
             match q.pop() {
                 None => break,
                 Some(mut s) => {
@@ -279,7 +289,7 @@ impl Blueprint {
                     let production = s.production();
                     // create (or not) new robots
                     for robot in &self.rules {
-                        if robot.can_produce(&s.inv) {
+                        if robot.can_produce(&s.inv) && !self.enough_robots(robot, &s.robots) {
                             let ns = s.new_with(&robot, &production);
                             if should_add(&ns, max) & !visited.contains(&ns.key()) {
                                 // println!("Adding extra {:?} robot {}", robot.generates, ns);
@@ -288,6 +298,10 @@ impl Blueprint {
                             }
                         }
                     }
+                    // waiting is pointless if every robot can be created now
+                    if self.rules.iter().all(|r| r.can_produce(&s.inv)) {
+                        continue;
+                    }
                     // original state is also a candidate (no new robots created)
                     s.produce();
                     if should_add(&s, max) & !visited.contains(&s.key()) {
@@ -299,37 +313,196 @@ impl Blueprint {
             }
         }
         // println!("{}: {}", self.id, max);
-        max as u32
+        Outcome::Complete(max)
     }
 }
 
-pub fn part1() -> u32 {
-    let blueprints: Vec<Blueprint> = load("data/day19.txt");
-    blueprints
-        .into_iter()
-        .map(|bp| bp.id * bp.produce(24))
-        .sum()
+// combines the outcome of every blueprint with `f`, whose identity is `init`; interrupted ones
+// count with their best so far, and the ones without any as the identity
+fn combine(
+    outcomes: impl Iterator<Item = Outcome<u32>>,
+    init: u32,
+    f: impl Fn(u32, u32) -> u32,
+) -> Outcome<u32> {
+    let (mut complete, mut known) = (true, false);
+    let mut acc = init;
+    for o in outcomes {
+        complete &= o.is_complete();
+        if let Some(value) = o.value() {
+            known = true;
+            acc = f(acc, value);
+        }
+    }
+    match (complete, known) {
+        (true, _) => Outcome::Complete(acc),
+        (false, true) => Outcome::Interrupted(Some(acc)),
+        (false, false) => Outcome::Interrupted(None),
+    }
+}
+
+pub fn part1_with(filename: &str, ctx: &Context) -> Outcome<u32> {
+    let blueprints: Vec<Blueprint> = load(filename);
+    combine(
+        blueprints
+            .iter()
+            .map(|bp| bp.produce(24, ctx).map(|geodes| bp.id * geodes)),
+        0,
+        |a, b| a + b,
+    )
+}
+
+pub fn part2_with(filename: &str, ctx: &Context) -> Outcome<u32> {
+    let blueprints: Vec<Blueprint> = load(filename);
+    combine(
+        blueprints[0..3].iter().map(|bp| bp.produce(32, ctx)),
+        1,
+        |a, b| a * b,
+    )
+}
+
+pub fn part1(filename: &str) -> u32 {
+    part1_with(filename, &Context::new()).unwrap()
 }
 
-pub fn part2() -> u32 {
-    let blueprints: Vec<Blueprint> = load("data/day19.txt");
-    blueprints[0..3]
-        .into_iter()
-        .map(|bp| bp.produce(32))
-        .product()
+pub fn part2(filename: &str) -> u32 {
+    part2_with(filename, &Context::new()).unwrap()
 }
 
+#[cfg(test)]
 mod tests {
+    use std::ops::Range;
+
+    use super::*;
+    use crate::check::differential;
+
+    // plain depth first search: pick the next robot to build and wait for it
+    fn reference(bp: &Blueprint, time: u32) -> u32 {
+        fn search(bp: &Blueprint, tl: u32, robots: [u32; 4], inv: [u32; 4]) -> u32 {
+            let mut best = inv[3] + robots[3] * tl;
+            for (r, robot) in bp.rules.iter().enumerate() {
+                let needs = [robot.needs.ore, robot.needs.clay, robot.needs.obsidian];
+                // minutes to wait until there is enough of everything
+                let wait = (0..3)
+                    .map(|i| match (needs[i].saturating_sub(inv[i]), robots[i]) {
+                        (0, _) => Some(0),
+                        (_, 0) => None,
+                        (missing, n) => Some((missing + n - 1) / n),
+                    })
+                    .try_fold(0, |w, i| i.map(|i| w.max(i)));
+                match wait {
+                    Some(wait) if wait + 1 < tl => {
+                        let mut inv = inv;
+                        for i in 0..4 {
+                            inv[i] += robots[i] * (wait + 1);
+                        }
+                        for i in 0..3 {
+                            inv[i] -= needs[i];
+                        }
+                        let mut robots = robots;
+                        robots[r] += 1;
+                        best = best.max(search(bp, tl - wait - 1, robots, inv));
+                    }
+                    _ => (),
+                }
+            }
+            best
+        }
+        search(bp, time, [1, 0, 0, 0], [0; 4])
+    }
+
+    fn blueprint(costs: &[u32; 6]) -> Blueprint {
+        format!(
+            "Blueprint 1: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.",
+            costs[0], costs[1], costs[2], costs[3], costs[4], costs[5]
+        )
+        .parse()
+        .unwrap()
+    }
+
+    fn geodes_differential(seeds: Range<u64>, max_time: i64) {
+        differential(
+            "day19",
+            seeds,
+            |rng| {
+                let costs = [
+                    rng.range(1, 4),
+                    rng.range(1, 4),
+                    rng.range(1, 4),
+                    rng.range(2, 12),
+                    rng.range(1, 4),
+                    rng.range(2, 12),
+                ];
+                Some((costs.map(|c| c as u32), rng.range(8, max_time) as u32))
+            },
+            |(costs, time)| {
+                let mut smaller = vec![];
+                if *time > 1 {
+                    smaller.push((*costs, time - 1));
+                }
+                for i in 0..costs.len() {
+                    if costs[i] > 1 {
+                        let mut costs = *costs;
+                        costs[i] -= 1;
+                        smaller.push((costs, *time));
+                    }
+                }
+                smaller
+            },
+            |(costs, time)| blueprint(costs).produce(*time, &Context::new()).unwrap(),
+            |(costs, time)| reference(&blueprint(costs), *time),
+        );
+    }
+
+    #[test]
+    fn test_differential() {
+        geodes_differential(0..30, 16);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_sweep() {
+        geodes_differential(0..300, 20);
+    }
+
+    // cheap robots soon leave more of every resource in stock than one of each robot
+    // costs; states like that still go on to build geode robots
+    #[test]
+    fn test_combine() {
+        let product = |outcomes: Vec<Outcome<u32>>| combine(outcomes.into_iter(), 1, |a, b| a * b);
+        assert_eq!(
+            product(vec![Outcome::Complete(2), Outcome::Complete(3)]),
+            Outcome::Complete(6)
+        );
+        assert_eq!(
+            product(vec![Outcome::Complete(2), Outcome::Interrupted(None)]),
+            Outcome::Interrupted(Some(2))
+        );
+        assert_eq!(
+            product(vec![Outcome::Interrupted(Some(4)), Outcome::Complete(3)]),
+            Outcome::Interrupted(Some(12))
+        );
+        assert_eq!(
+            product(vec![Outcome::Interrupted(None), Outcome::Interrupted(None)]),
+            Outcome::Interrupted(None)
+        );
+    }
+
+    #[test]
+    fn test_large_inventory() {
+        let bp = blueprint(&[1, 2, 1, 3, 1, 5]);
+        assert_eq!(bp.produce(13, &Context::new()).unwrap(), 3);
+    }
+
     #[test]
     fn test_part1() {
-        let quality = super::part1();
+        let quality = super::part1("data/day19.txt");
         println!("Total quality: {}", quality);
         assert_eq!(quality, 1466);
     }
 
     #[test]
     fn test_part2() {
-        let product = super::part2();
+        let product = super::part2("data/day19.txt");
         println!("Product: {}", product);
         assert_eq!(product, 8250);
     }
@@ -1,190 +1,460 @@
-use std::str::FromStr;
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    str::FromStr,
+};
 
 use crate::util::load;
 
-#[derive(Debug, Copy, Clone)]
-pub enum Choice {
-    Rock,
-    Paper,
-    Scissors,
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    Lose,
+    Draw,
+    Win,
 }
 
-impl FromStr for Choice {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "A" | "X" => Ok(Self::Rock),
-            "B" | "Y" => Ok(Self::Paper),
-            "C" | "Z" => Ok(Self::Scissors),
-            _ => Err(()),
+impl GameResult {
+    pub fn score(&self) -> u32 {
+        match self {
+            Self::Lose => 0,
+            Self::Draw => 3,
+            Self::Win => 6,
         }
     }
 }
 
-impl Choice {
-    fn score(&self) -> u32 {
-        match self {
-            Self::Rock => 1,
-            Self::Paper => 2,
-            Self::Scissors => 3,
-        }
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shape {
+    pub name: String,
+    pub score: u32,
+    pub opponent: char, // letter in the first column
+    pub response: char, // letter in the second column
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RulesError {
+    EvenShapes(usize),
+    TooManyShapes(usize),
+    BadLine(usize),
+    DuplicateLetter(char),
+}
 
-    fn wins_from(&self, other: &Self) -> bool {
+impl Display for RulesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Rock => {
-                if let Self::Scissors = other {
-                    true
-                } else {
-                    false
-                }
-            }
-            Self::Paper => {
-                if let Self::Rock = other {
-                    true
-                } else {
-                    false
-                }
-            }
-            Self::Scissors => {
-                if let Self::Paper = other {
-                    true
-                } else {
-                    false
-                }
+            Self::EvenShapes(n) => write!(f, "{} shapes, need an odd number", n),
+            Self::TooManyShapes(n) => write!(f, "{} shapes, at most 13 fit in the alphabet", n),
+            Self::BadLine(l) => {
+                write!(f, "line {}: expected `<name> <letter> <letter> <score>`", l)
             }
+            Self::DuplicateLetter(c) => write!(f, "letter {} used twice", c),
         }
     }
+}
+
+// the strategy guide does not fit the rules
+#[derive(Debug, PartialEq, Eq)]
+pub enum GuideError {
+    // not two letters separated by a space
+    BadLine(usize),
+    // a letter that means nothing in that column
+    UnknownLetter { line: usize, letter: char },
+}
 
-    fn expect_result(&self, expect: &GameResult) -> Self {
+impl Display for GuideError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Rock => match expect {
-                GameResult::Lose => Self::Scissors,
-                GameResult::Draw => Self::Rock,
-                GameResult::Win => Self::Paper,
-            },
-            Self::Paper => match expect {
-                GameResult::Lose => Self::Rock,
-                GameResult::Draw => Self::Paper,
-                GameResult::Win => Self::Scissors,
-            },
-            Self::Scissors => match expect {
-                GameResult::Lose => Self::Paper,
-                GameResult::Draw => Self::Scissors,
-                GameResult::Win => Self::Rock,
-            },
+            Self::BadLine(l) => write!(f, "line {}: expected `<letter> <letter>`", l),
+            Self::UnknownLetter { line, letter } => {
+                write!(f, "line {}: unknown letter {}", line, letter)
+            }
         }
     }
 }
 
-pub enum GameResult {
-    Lose,
-    Draw,
-    Win,
+// Cyclic rock paper scissors: every shape beats the (n - 1) / 2 shapes before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub shapes: Vec<Shape>,
+    pub outcomes: [char; 3], // second column letters asking to lose, draw or win
 }
 
-impl FromStr for GameResult {
-    type Err = ();
+const CLASSIC: &str = "Rock A X 1
+Paper B Y 2
+Scissors C Z 3
+";
+
+// one shape per line, in cycle order: `<name> <opponent letter> <response letter> <score>`,
+// optionally followed by `outcomes <lose> <draw> <win>` (defaults to X, Y and Z)
+impl FromStr for Rules {
+    type Err = RulesError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "X" => Ok(Self::Lose),
-            "Y" => Ok(Self::Draw),
-            "Z" => Ok(Self::Win),
-            _ => Err(()),
+        let letter = |t: &str| {
+            Some(t)
+                .filter(|t| t.len() == 1)
+                .and_then(|t| t.chars().next())
+        };
+        let mut shapes = vec![];
+        let mut outcomes = ['X', 'Y', 'Z'];
+        for (i, line) in s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens[..] {
+                ["outcomes", lose, draw, win] => {
+                    let letters = [lose, draw, win].map(letter);
+                    if letters.contains(&None) {
+                        return Err(RulesError::BadLine(i + 1));
+                    }
+                    outcomes = letters.map(Option::unwrap);
+                }
+                [name, opponent, response, score] => {
+                    let shape = letter(opponent)
+                        .zip(letter(response))
+                        .zip(score.parse().ok())
+                        .map(|((opponent, response), score)| Shape {
+                            name: name.to_owned(),
+                            score,
+                            opponent,
+                            response,
+                        });
+                    shapes.push(shape.ok_or(RulesError::BadLine(i + 1))?);
+                }
+                _ => return Err(RulesError::BadLine(i + 1)),
+            }
         }
+        Rules::new(shapes, outcomes)
     }
 }
 
-impl GameResult {
-    fn score(&self) -> u32 {
-        match self {
-            Self::Lose => 0,
-            Self::Draw => 3,
-            Self::Win => 6,
+impl Rules {
+    pub fn new(shapes: Vec<Shape>, outcomes: [char; 3]) -> Result<Self, RulesError> {
+        if shapes.len().is_multiple_of(2) {
+            return Err(RulesError::EvenShapes(shapes.len()));
         }
+        for letters in [
+            shapes.iter().map(|s| s.opponent).collect::<Vec<_>>(),
+            shapes.iter().map(|s| s.response).collect(),
+            outcomes.to_vec(),
+        ] {
+            for (i, c) in letters.iter().enumerate() {
+                if letters[..i].contains(c) {
+                    return Err(RulesError::DuplicateLetter(*c));
+                }
+            }
+        }
+        Ok(Rules { shapes, outcomes })
     }
-}
 
-#[derive(Debug, Copy, Clone)]
-pub struct Game1 {
-    op: Choice,
-    me: Choice,
-}
+    // the original game
+    pub fn classic() -> Self {
+        CLASSIC.parse().unwrap()
+    }
 
-impl FromStr for Game1 {
-    type Err = ();
+    // `n` shapes scoring 1 to `n`, played with A, B, ... against ..., Y, Z
+    pub fn cyclic(n: usize) -> Result<Self, RulesError> {
+        if n > 13 {
+            return Err(RulesError::TooManyShapes(n));
+        }
+        let shapes = (0..n)
+            .map(|i| Shape {
+                name: format!("Shape{}", i + 1),
+                score: i as u32 + 1,
+                opponent: (b'A' + i as u8) as char,
+                response: (b'Z' + 1 - n as u8 + i as u8) as char,
+            })
+            .collect();
+        Rules::new(shapes, ['X', 'Y', 'Z'])
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let choices: Vec<Choice> = s.split(' ').map(|c| c.parse().unwrap()).collect();
-        Ok(Game1 {
-            op: choices[0],
-            me: choices[1],
-        })
+    pub fn load(filename: &str) -> Result<Self, RulesError> {
+        fs::read_to_string(filename).unwrap().parse()
     }
-}
 
-impl Game1 {
-    fn score(&self) -> u32 {
-        let game_score = if self.me.wins_from(&self.op) {
-            6
-        } else if self.op.wins_from(&self.me) {
-            0
-        } else {
-            3
-        };
-        game_score + self.me.score()
+    pub fn result(&self, me: usize, op: usize) -> GameResult {
+        let n = self.shapes.len();
+        match (me + n - op) % n {
+            0 => GameResult::Draw,
+            d if d <= n / 2 => GameResult::Win,
+            _ => GameResult::Lose,
+        }
+    }
+
+    pub fn score(&self, me: usize, op: usize) -> u32 {
+        self.shapes[me].score + self.result(me, op).score()
+    }
+
+    // best scoring shape to play against `op` for the expected result
+    pub fn choose(&self, op: usize, expect: GameResult) -> usize {
+        (0..self.shapes.len())
+            .filter(|&me| self.result(me, op) == expect)
+            .max_by_key(|&me| self.shapes[me].score)
+            .unwrap()
+    }
+
+    pub fn opponent(&self, c: char) -> Option<usize> {
+        self.shapes.iter().position(|s| s.opponent == c)
+    }
+
+    pub fn response(&self, c: char) -> Option<usize> {
+        self.shapes.iter().position(|s| s.response == c)
+    }
+
+    pub fn outcome(&self, c: char) -> Option<GameResult> {
+        let results = [GameResult::Lose, GameResult::Draw, GameResult::Win];
+        self.outcomes
+            .iter()
+            .position(|&o| o == c)
+            .map(|i| results[i])
     }
 }
 
-pub struct Game2 {
-    op: Choice,
-    expect: GameResult,
+// the guide as (line, opponent shape, second letter)
+fn guide(filename: &str, rules: &Rules) -> Result<Vec<(usize, usize, char)>, GuideError> {
+    load::<String>(filename)
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(i, l)| {
+            let line = i + 1;
+            let mut chars = l.chars();
+            let (op, me) = match (chars.next(), chars.next(), chars.next(), chars.next()) {
+                (Some(op), Some(' '), Some(me), None) => (op, me),
+                _ => return Err(GuideError::BadLine(line)),
+            };
+            let op = rules
+                .opponent(op)
+                .ok_or(GuideError::UnknownLetter { line, letter: op })?;
+            Ok((line, op, me))
+        })
+        .collect()
 }
 
-impl FromStr for Game2 {
-    type Err = ();
+// second column is the shape to play
+pub fn score_moves(filename: &str, rules: &Rules) -> Result<u32, GuideError> {
+    guide(filename, rules)?
+        .into_iter()
+        .map(|(line, op, me)| {
+            let me = rules
+                .response(me)
+                .ok_or(GuideError::UnknownLetter { line, letter: me })?;
+            Ok(rules.score(me, op))
+        })
+        .sum()
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let choices: Vec<&str> = s.split(' ').collect();
-        Ok(Game2 {
-            op: choices[0].parse::<Choice>().unwrap(),
-            expect: choices[1].parse::<GameResult>().unwrap(),
+// second column is the result to get
+pub fn score_outcomes(filename: &str, rules: &Rules) -> Result<u32, GuideError> {
+    guide(filename, rules)?
+        .into_iter()
+        .map(|(line, op, expect)| {
+            let expect = rules.outcome(expect).ok_or(GuideError::UnknownLetter {
+                line,
+                letter: expect,
+            })?;
+            Ok(rules.score(rules.choose(op, expect), op))
         })
+        .sum()
+}
+
+// how the second column of the guide was meant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mapping {
+    // the response letter of `rules.shapes[i]` means shape `v[i]`
+    Shapes(Vec<usize>),
+    // the letters are the results to get
+    Outcomes,
+}
+
+#[derive(Debug)]
+pub struct Inference {
+    pub consistent: Vec<Mapping>,
+    // `None` when no mapping applies to the guide at all
+    pub best: Option<(Mapping, u32)>,
+    pub worst: Option<(Mapping, u32)>,
+}
+
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+    let mut all = vec![];
+    for p in permutations(n - 1) {
+        for i in 0..n {
+            let mut p = p.clone();
+            p.insert(i, n - 1);
+            all.push(p);
+        }
     }
+    all
 }
 
-impl Game2 {
-    fn score(&self) -> u32 {
-        self.expect.score() + self.op.expect_result(&self.expect).score()
+// scores the guide under every mapping (all n! shape permutations, so keep `n` small)
+// and keeps the ones adding up to the claimed total; the shape mappings only apply when
+// every second letter is a response letter, the outcome one when every letter is a result
+pub fn infer(filename: &str, rules: &Rules, claimed: u32) -> Result<Inference, GuideError> {
+    let n = rules.shapes.len();
+    let guide: Vec<(usize, char)> = guide(filename, rules)?
+        .into_iter()
+        .map(|(_, op, me)| (op, me))
+        .collect();
+    let mut scored: Vec<(Mapping, u32)> = vec![];
+    // how often each opponent shape meets each response letter
+    let counts = guide
+        .iter()
+        .try_fold(vec![vec![0; n]; n], |mut counts, &(op, me)| {
+            counts[op][rules.response(me)?] += 1;
+            Some(counts)
+        });
+    if let Some(counts) = counts {
+        scored.extend(permutations(n).into_iter().map(|p| {
+            let score = (0..n)
+                .flat_map(|op| (0..n).map(move |me| (op, me)))
+                .map(|(op, me)| counts[op][me] * rules.score(p[me], op))
+                .sum();
+            (Mapping::Shapes(p), score)
+        }));
     }
+    let outcome_score: Option<u32> = guide
+        .iter()
+        .map(|&(op, me)| Some(rules.score(rules.choose(op, rules.outcome(me)?), op)))
+        .sum();
+    if let Some(score) = outcome_score {
+        scored.push((Mapping::Outcomes, score));
+    }
+    Ok(Inference {
+        consistent: scored
+            .iter()
+            .filter(|(_, score)| *score == claimed)
+            .map(|(m, _)| m.clone())
+            .collect(),
+        best: scored.iter().max_by_key(|(_, score)| score).cloned(),
+        worst: scored.iter().min_by_key(|(_, score)| score).cloned(),
+    })
 }
 
-pub fn part1() -> u32 {
-    let games: Vec<Game1> = load("data/day2.txt");
-    games.into_iter().map(|g| g.score()).sum()
+pub fn part1(filename: &str) -> Result<u32, GuideError> {
+    score_moves(filename, &Rules::classic())
 }
 
-pub fn part2() -> u32 {
-    let games: Vec<Game2> = load("data/day2.txt");
-    games.into_iter().map(|g| g.score()).sum()
+pub fn part2(filename: &str) -> Result<u32, GuideError> {
+    score_outcomes(filename, &Rules::classic())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::TempInput;
+
+    #[test]
+    fn test_rules() {
+        let rpsls: Rules = "Rock A V 1
+            Spock B W 5
+            Paper C X 2
+            Lizard D Y 4
+            Scissors E Z 3"
+            .parse()
+            .unwrap();
+        let shape = |name| rpsls.shapes.iter().position(|s| s.name == name).unwrap();
+        assert_eq!(
+            rpsls.result(shape("Spock"), shape("Scissors")),
+            GameResult::Win
+        );
+        assert_eq!(
+            rpsls.result(shape("Lizard"), shape("Spock")),
+            GameResult::Win
+        );
+        assert_eq!(
+            rpsls.result(shape("Rock"), shape("Paper")),
+            GameResult::Lose
+        );
+        // scissors and lizard beat paper, paper beats spock
+        assert_eq!(
+            rpsls.choose(shape("Paper"), GameResult::Lose),
+            shape("Spock")
+        );
+        assert_eq!(
+            rpsls.choose(shape("Paper"), GameResult::Win),
+            shape("Lizard")
+        );
+
+        assert_eq!(Rules::cyclic(3).unwrap().result(0, 2), GameResult::Win);
+        assert_eq!(Rules::cyclic(5).unwrap().response('V'), Some(0));
+        assert_eq!(Rules::cyclic(4), Err(RulesError::EvenShapes(4)));
+        assert_eq!("Rock A X".parse::<Rules>(), Err(RulesError::BadLine(1)));
+        assert_eq!(
+            "Rock A X 1\nPaper A Y 2\nScissors C Z 3".parse::<Rules>(),
+            Err(RulesError::DuplicateLetter('A'))
+        );
+    }
+
+    #[test]
+    fn test_infer() {
+        let rules = Rules::classic();
+        let inference = infer("data/day2.txt", &rules, 11873).unwrap();
+        assert_eq!(inference.consistent, vec![Mapping::Shapes(vec![0, 1, 2])]);
+        let inference = infer("data/day2.txt", &rules, 12014).unwrap();
+        assert_eq!(inference.consistent, vec![Mapping::Outcomes]);
+        assert!(inference.best.unwrap().1 >= 12014 && inference.worst.unwrap().1 <= 11873);
+        assert_eq!(
+            infer("data/day2.txt", &rules, 0).unwrap().consistent,
+            vec![]
+        );
+
+        // V and W mean shapes, but no result
+        let guide = TempInput::new("A V\nB X\nC W\n").unwrap();
+        let rules = Rules::cyclic(5).unwrap();
+        assert!((0..100).all(|claimed| {
+            !infer(guide.filename(), &rules, claimed)
+                .unwrap()
+                .consistent
+                .contains(&Mapping::Outcomes)
+        }));
+        // results only
+        let rules: Rules = "Rock A X 1\nPaper B Y 2\nScissors C Z 3\noutcomes L D W"
+            .parse()
+            .unwrap();
+        let guide = TempInput::new("A L\nB W\n").unwrap();
+        let inference = infer(guide.filename(), &rules, 3 + 9).unwrap();
+        assert_eq!(inference.consistent, vec![Mapping::Outcomes]);
+        assert_eq!(inference.best, Some((Mapping::Outcomes, 12)));
+    }
+
+    #[test]
+    fn test_guide_errors() {
+        let rules = Rules::classic();
+        let guide = TempInput::new("A X\nA Q\n").unwrap();
+        assert_eq!(
+            score_moves(guide.filename(), &rules),
+            Err(GuideError::UnknownLetter {
+                line: 2,
+                letter: 'Q'
+            })
+        );
+        let guide = TempInput::new("D Y\n").unwrap();
+        assert_eq!(
+            score_outcomes(guide.filename(), &rules),
+            Err(GuideError::UnknownLetter {
+                line: 1,
+                letter: 'D'
+            })
+        );
+        let guide = TempInput::new("AX\n").unwrap();
+        assert_eq!(
+            infer(guide.filename(), &rules, 0).map(|i| i.consistent),
+            Err(GuideError::BadLine(1))
+        );
+    }
 
     #[test]
     fn test_part1() {
-        let score = part1();
+        let score = part1("data/day2.txt").unwrap();
         println!("Score is {}", score);
         assert_eq!(score, 11873);
     }
 
     #[test]
     fn test_part2() {
-        let score = part2();
+        let score = part2("data/day2.txt").unwrap();
         println!("Score is {}", score);
         assert_eq!(score, 12014);
     }
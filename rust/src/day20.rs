@@ -65,15 +65,15 @@ fn load_items(filename: &str, multiplier: i64) -> Vec<Item> {
     items
 }
 
-pub fn part1() -> i64 {
-    let mut numbers: Vec<Item> = load_items("data/day20.txt", 1);
+pub fn part1(filename: &str) -> i64 {
+    let mut numbers: Vec<Item> = load_items(filename, 1);
     mix(&mut numbers);
     score(&numbers)
 }
 
-pub fn part2() -> i64 {
+pub fn part2(filename: &str) -> i64 {
     let multiplier = 811589153;
-    let mut numbers = load_items("data/day20.txt", multiplier);
+    let mut numbers = load_items(filename, multiplier);
     for _ in 0..10 {
         mix(&mut numbers);
     }
@@ -95,7 +95,88 @@ pub fn do_mix(data: &[i64]) -> Vec<i64> {
     data.into_iter().map(|i| i.v).collect()
 }
 
+#[cfg(test)]
 mod tests {
+    use std::ops::Range;
+
+    use super::*;
+    use crate::check::{differential, remove_one};
+
+    // the obvious way to mix: move the item and wrap its new index around the
+    // other `len - 1` items
+    fn reference_mix(values: &[i64], rounds: usize) -> Vec<i64> {
+        let len = values.len();
+        let mut order: Vec<usize> = (0..len).collect();
+        for _ in 0..rounds {
+            for id in 0..len {
+                let idx = order.iter().position(|&i| i == id).unwrap();
+                order.remove(idx);
+                let new_idx = (idx as i64 + values[id]).rem_euclid(len as i64 - 1);
+                order.insert(new_idx as usize, id);
+            }
+        }
+        order.into_iter().map(|id| values[id]).collect()
+    }
+
+    // only the circular order matters, starting from the zero
+    fn from_zero(mut values: Vec<i64>) -> Vec<i64> {
+        let p0 = values.iter().position(|&v| v == 0).unwrap();
+        values.rotate_left(p0);
+        values
+    }
+
+    fn fast_mix(values: &[i64], rounds: usize) -> Vec<i64> {
+        let mut items: Vec<Item> = values
+            .iter()
+            .enumerate()
+            .map(|(id, &v)| Item { v, id: id as i64 })
+            .collect();
+        for _ in 0..rounds {
+            mix(&mut items);
+        }
+        items.into_iter().map(|i| i.v).collect()
+    }
+
+    fn mix_differential(seeds: Range<u64>, max_len: i64) {
+        for (rounds, multiplier) in [(1, 1), (10, 811589153)] {
+            differential(
+                "day20 mix",
+                seeds.clone(),
+                |rng| {
+                    let len = rng.range(2, max_len) as usize;
+                    let zero = rng.below(len);
+                    let values = (0..len)
+                        .map(|i| match i == zero {
+                            true => 0,
+                            // the only zero, the circular order is read from it
+                            false => rng.range(1, 30) * rng.pick(&[-1, 1]) * multiplier,
+                        })
+                        .collect::<Vec<i64>>();
+                    Some(values)
+                },
+                |values| {
+                    remove_one(values)
+                        .into_iter()
+                        .filter(|v| v.len() >= 2 && v.iter().filter(|&&x| x == 0).count() == 1)
+                        .collect()
+                },
+                |values| from_zero(fast_mix(values, rounds)),
+                |values| from_zero(reference_mix(values, rounds)),
+            );
+        }
+    }
+
+    #[test]
+    fn test_mix_differential() {
+        mix_differential(0..300, 8);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_mix_sweep() {
+        mix_differential(0..2000, 10);
+    }
+
     #[test]
     fn test_mix() {
         assert_eq!(super::do_mix(&[0, 0, 0]), vec![0, 0, 0]);
@@ -133,14 +214,14 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let sum = super::part1();
+        let sum = super::part1("data/day20.txt");
         println!("Sum: {}", sum);
         assert_eq!(sum, 11123);
     }
 
     #[test]
     fn test_part2() {
-        let sum = super::part2();
+        let sum = super::part2("data/day20.txt");
         println!("Sum: {}", sum);
         assert_eq!(sum, 4248669215955);
     }
@@ -121,14 +121,14 @@ fn reverse_yell(name: &str, num: u64, monkeys: &MonkeyMap, numbers: &NumberMap)
     }
 }
 
-pub fn part1() -> u64 {
-    let (mut numbers, mut monkeys) = load_monkeys("data/day21.txt");
+pub fn part1(filename: &str) -> u64 {
+    let (mut numbers, mut monkeys) = load_monkeys(filename);
     yell(&mut monkeys, &mut numbers);
     numbers["root"]
 }
 
-pub fn part2() -> u64 {
-    let (mut numbers, mut monkeys) = load_monkeys("data/day21.txt");
+pub fn part2(filename: &str) -> u64 {
+    let (mut numbers, mut monkeys) = load_monkeys(filename);
     let _human = numbers.remove("humn").unwrap();
     let root = monkeys.remove("root").unwrap();
     // forward partial solve
@@ -145,14 +145,14 @@ pub fn part2() -> u64 {
 mod tests {
     #[test]
     fn test_part1() {
-        let num = super::part1();
+        let num = super::part1("data/day21.txt");
         println!("Number: {}", num);
         assert_eq!(num, 83056452926300);
     }
 
     #[test]
     fn test_part2() {
-        let num = super::part2();
+        let num = super::part2("data/day21.txt");
         println!("Number: {}", num);
         assert_eq!(num, 3469704905529);
     }
@@ -258,14 +258,14 @@ impl Notes {
     }
 }
 
-pub fn part1() -> usize {
-    let mut notes = Notes::load("data/day22.txt", false);
+pub fn part1(filename: &str) -> usize {
+    let mut notes = Notes::load(filename, false);
     notes.walk();
     notes.map.password()
 }
 
-pub fn part2() -> usize {
-    let mut notes = Notes::load("data/day22.txt", true);
+pub fn part2(filename: &str) -> usize {
+    let mut notes = Notes::load(filename, true);
     notes.walk();
     notes.map.password()
 }
@@ -273,14 +273,14 @@ pub fn part2() -> usize {
 mod tests {
     #[test]
     fn test_part1() {
-        let password = super::part1();
+        let password = super::part1("data/day22.txt");
         println!("Password: {}", password);
         assert_eq!(password, 88226);
     }
 
     #[test]
     fn test_part2() {
-        let password = super::part2();
+        let password = super::part2("data/day22.txt");
         println!("Password: {}", password);
         assert_eq!(password, 57305);
     }
@@ -180,14 +180,14 @@ impl Grid {
     }
 }
 
-pub fn part1() -> usize {
-    let mut grid = Grid::load("data/day23.txt");
+pub fn part1(filename: &str) -> usize {
+    let mut grid = Grid::load(filename);
     grid.move_rounds(10);
     grid.empty()
 }
 
-pub fn part2() -> usize {
-    let mut grid = Grid::load("data/day23.txt");
+pub fn part2(filename: &str) -> usize {
+    let mut grid = Grid::load(filename);
     let rounds = grid.move_expand();
     rounds
 }
@@ -195,14 +195,14 @@ pub fn part2() -> usize {
 mod tests {
     #[test]
     fn test_part1() {
-        let num = super::part1();
+        let num = super::part1("data/day23.txt");
         println!("Number of empty ground tiles: {}", num);
         assert_eq!(num, 4025);
     }
 
     #[test]
     fn test_part2() {
-        let rounds = super::part2();
+        let rounds = super::part2("data/day23.txt");
         println!("Number of rounds: {}", rounds);
         assert_eq!(rounds, 935);
     }
@@ -240,19 +240,19 @@ impl MultiMap {
     }
 }
 
-pub fn part1() -> usize {
+pub fn part1(filename: &str) -> usize {
     // Grid repeats every 12 iterations for test
     // let mut mm = MultiMap::build(Map::load("data/day24-test.txt"), 12);
     // Grid repeats every 600 iterations for actual
-    let mut mm = MultiMap::build(Map::load("data/day24.txt"), 600);
+    let mut mm = MultiMap::build(Map::load(filename), 600);
     let start = (0, 0);
     let finish = (mm.width - 1, mm.height + 1);
     mm.find_path(start, finish, WalkDirection::Forward, 0)
 }
 
-pub fn part2() -> usize {
+pub fn part2(filename: &str) -> usize {
     // let mut mm = MultiMap::build(Map::load("data/day24-test.txt"), 12);
-    let mut mm = MultiMap::build(Map::load("data/day24.txt"), 600);
+    let mut mm = MultiMap::build(Map::load(filename), 600);
     let start = (0, 0);
     let finish = (mm.width - 1, mm.height + 1);
     let mut times = Vec::new();
@@ -265,14 +265,14 @@ pub fn part2() -> usize {
 mod tests {
     #[test]
     fn test_part1() {
-        let minutes = super::part1();
+        let minutes = super::part1("data/day24.txt");
         println!("Minutes needed: {}", minutes);
         assert_eq!(minutes, 253);
     }
 
     #[test]
     fn test_part2() {
-        let minutes = super::part2();
+        let minutes = super::part2("data/day24.txt");
         println!("Minutes needed: {}", minutes);
         assert_eq!(minutes, 794);
     }
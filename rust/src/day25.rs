@@ -55,8 +55,8 @@ fn ufans(mut n: i64) -> String {
     s.iter().rev().collect()
 }
 
-pub fn part1() -> String {
-    let n = load::<String>("data/day25.txt")
+pub fn part1(filename: &str) -> String {
+    let n = load::<String>(filename)
         .into_iter()
         .map(|s| snafu(&s))
         .sum();
@@ -66,7 +66,7 @@ pub fn part1() -> String {
 mod tests {
     #[test]
     fn test_part1() {
-        let num = super::part1();
+        let num = super::part1("data/day25.txt");
         println!("SNAFU number: {}", num);
         assert_eq!(num, "2-21=02=1-121-2-11-0");
     }
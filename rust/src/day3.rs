@@ -1,83 +1,216 @@
-use std::collections::HashSet;
-
-use std::str::FromStr;
+use std::fmt::{Display, Formatter};
 
 use crate::util::load;
 
-struct Rucksack {
-    comp1: HashSet<char>,
-    comp2: HashSet<char>,
+#[derive(Debug, PartialEq, Eq)]
+pub enum RucksackError {
+    // the line does not split into equal compartments
+    Length { line: usize, len: usize },
+    // not an item, items are a-z and A-Z
+    Item { line: usize, pos: usize, c: char },
 }
 
-impl FromStr for Rucksack {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut comp1 = HashSet::new();
-        let mut comp2 = HashSet::new();
-        for (i, c) in s.chars().enumerate() {
-            if i < s.len() / 2 {
-                comp1.insert(c);
-            } else {
-                comp2.insert(c);
+impl Display for RucksackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Length { line, len } => {
+                write!(
+                    f,
+                    "line {}: {} items do not fit the compartments",
+                    line, len
+                )
+            }
+            Self::Item { line, pos, c } => {
+                write!(f, "line {}, position {}: invalid item {:?}", line, pos, c)
             }
         }
-        Ok(Rucksack { comp1, comp2 })
     }
 }
 
-pub fn priority(c: &char) -> u32 {
+pub fn priority(c: char) -> Option<u32> {
     match c {
-        'A'..='Z' => Into::<u32>::into(*c) - Into::<u32>::into('A') + 27,
-        'a'..='z' => Into::<u32>::into(*c) - Into::<u32>::into('a') + 1,
-        _ => 0,
+        'A'..='Z' => Some(c as u32 - 'A' as u32 + 27),
+        'a'..='z' => Some(c as u32 - 'a' as u32 + 1),
+        _ => None,
     }
 }
 
-pub fn part1() -> u32 {
-    let rucksacks: Vec<Rucksack> = load("data/day3.txt");
-    rucksacks
-        .into_iter()
-        .map(|r| {
-            r.comp1
-                .intersection(&r.comp2)
-                .map(|c| priority(c))
-                .sum::<u32>()
+// set of items, bit `n` for the item of priority `n`
+fn priorities(mut set: u64) -> u32 {
+    let mut sum = 0;
+    while set != 0 {
+        sum += set.trailing_zeros();
+        set &= set - 1;
+    }
+    sum
+}
+
+#[derive(Debug)]
+pub struct Rucksack {
+    pub line: usize,
+    pub items: Vec<char>,
+    pub compartments: Vec<u64>,
+}
+
+impl Rucksack {
+    fn all(&self) -> u64 {
+        self.compartments.iter().fold(0, |acc, c| acc | c)
+    }
+
+    // items in more than one compartment
+    pub fn misplaced(&self) -> u64 {
+        let mut seen = 0;
+        let mut twice = 0;
+        for c in &self.compartments {
+            twice |= seen & c;
+            seen |= c;
+        }
+        twice
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Misplaced {
+    pub line: usize,
+    pub pos: usize,
+    pub item: char,
+}
+
+impl Display for Misplaced {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, position {}: {} (priority {})",
+            self.line,
+            self.pos,
+            self.item,
+            priority(self.item).unwrap()
+        )
+    }
+}
+
+// every line split into `compartments` equal parts; lines and positions count from 1
+pub fn rucksacks(lines: &[String], compartments: usize) -> Result<Vec<Rucksack>, RucksackError> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            let line = i + 1;
+            let items: Vec<char> = l.chars().collect();
+            if !items.len().is_multiple_of(compartments) {
+                return Err(RucksackError::Length {
+                    line,
+                    len: items.len(),
+                });
+            }
+            let size = items.len() / compartments;
+            let mut sets = vec![0; compartments];
+            for (pos, &c) in items.iter().enumerate() {
+                let p = priority(c).ok_or(RucksackError::Item {
+                    line,
+                    pos: pos + 1,
+                    c,
+                })?;
+                sets[pos / size] |= 1 << p;
+            }
+            Ok(Rucksack {
+                line,
+                items,
+                compartments: sets,
+            })
         })
-        .sum()
+        .collect()
+}
+
+pub fn load_rucksacks(filename: &str, compartments: usize) -> Result<Vec<Rucksack>, RucksackError> {
+    rucksacks(&load(filename), compartments)
+}
+
+// every copy of an item found after the compartment it first showed up in
+pub fn diagnostics(rucksacks: &[Rucksack]) -> Vec<Misplaced> {
+    let mut misplaced = vec![];
+    for r in rucksacks {
+        let size = r.items.len() / r.compartments.len();
+        let twice = r.misplaced();
+        for (pos, &item) in r.items.iter().enumerate() {
+            let bit = 1 << priority(item).unwrap();
+            let first = r.compartments.iter().position(|c| c & bit != 0).unwrap();
+            if twice & bit != 0 && pos / size != first {
+                misplaced.push(Misplaced {
+                    line: r.line,
+                    pos: pos + 1,
+                    item,
+                });
+            }
+        }
+    }
+    misplaced
+}
+
+pub fn misplaced_priorities(rucksacks: &[Rucksack]) -> u32 {
+    rucksacks.iter().map(|r| priorities(r.misplaced())).sum()
 }
 
-pub fn part2() -> u32 {
-    let rucksacks: Vec<Rucksack> = load("data/day3.txt");
+// items carried by everybody in each group of `size` rucksacks
+pub fn badge_priorities(rucksacks: &[Rucksack], size: usize) -> u32 {
     rucksacks
-        .chunks(3)
-        .map(|chunk| {
-            chunk
-                .into_iter()
-                .map(|r| r.comp1.union(&r.comp2).copied().collect())
-                .reduce(|acc: HashSet<char>, r| acc.intersection(&r).copied().collect())
-                .unwrap()
-                .into_iter()
-                .map(|c| priority(&c))
-                .sum::<u32>()
-        })
+        .chunks(size)
+        .map(|group| priorities(group.iter().fold(!0, |acc, r| acc & r.all())))
         .sum()
 }
 
+pub fn part1(filename: &str) -> Result<u32, RucksackError> {
+    Ok(misplaced_priorities(&load_rucksacks(filename, 2)?))
+}
+
+pub fn part2(filename: &str) -> Result<u32, RucksackError> {
+    Ok(badge_priorities(&load_rucksacks(filename, 2)?, 3))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_rucksacks() {
+        let lines = ["aBcaBd", "xyzxaq", "aqBxya"].map(|l| l.to_owned());
+        let three = rucksacks(&lines, 3).unwrap();
+        assert_eq!(misplaced_priorities(&three), 1 + 28 + 24 + 1);
+        let misplaced = |line, pos, item| Misplaced { line, pos, item };
+        assert_eq!(
+            diagnostics(&three),
+            vec![
+                misplaced(1, 4, 'a'),
+                misplaced(1, 5, 'B'),
+                misplaced(2, 4, 'x'),
+                misplaced(3, 6, 'a'),
+            ]
+        );
+        assert_eq!(badge_priorities(&three, 3), 1);
+        assert_eq!(
+            rucksacks(&lines, 4).unwrap_err(),
+            RucksackError::Length { line: 1, len: 6 }
+        );
+        assert_eq!(
+            rucksacks(&["ab1d".to_owned()], 2).unwrap_err(),
+            RucksackError::Item {
+                line: 1,
+                pos: 3,
+                c: '1'
+            }
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let priority_sum = part1();
+        let priority_sum = part1("data/day3.txt").unwrap();
         println!("Priority sum is {}", priority_sum);
         assert_eq!(priority_sum, 7674);
     }
 
     #[test]
     fn test_part2() {
-        let priority_sum = part2();
+        let priority_sum = part2("data/day3.txt").unwrap();
         println!("Priority sum is {}", priority_sum);
         assert_eq!(priority_sum, 2805);
     }
@@ -1,53 +1,222 @@
+use std::str::FromStr;
+
 use crate::util::load;
 
-type Pair = Vec<u32>;
+// inclusive range of sections
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Range {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl FromStr for Range {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s.split_once('-').ok_or(())?;
+        let (from, to) = (from.parse().map_err(|_| ())?, to.parse().map_err(|_| ())?);
+        if from > to {
+            return Err(());
+        }
+        Ok(Range { from, to })
+    }
+}
+
+impl Range {
+    pub fn contains(&self, other: &Range) -> bool {
+        self.from <= other.from && other.to <= self.to
+    }
+
+    pub fn overlaps(&self, other: &Range) -> bool {
+        self.from <= other.to && other.from <= self.to
+    }
+}
+
+// the assignments of one line, a pair in the puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub ranges: Vec<Range>,
+}
+
+impl FromStr for Group {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ranges = s.split(',').map(|r| r.parse()).collect::<Result<_, _>>()?;
+        Ok(Group { ranges })
+    }
+}
+
+impl Group {
+    fn pairs(&self) -> impl Iterator<Item = (&Range, &Range)> {
+        self.ranges
+            .iter()
+            .enumerate()
+            .flat_map(|(i, r1)| self.ranges[i + 1..].iter().map(move |r2| (r1, r2)))
+    }
+
+    // one of the elves does not need to bother
+    pub fn any_contains(&self) -> bool {
+        self.pairs()
+            .any(|(r1, r2)| r1.contains(r2) || r2.contains(r1))
+    }
+
+    pub fn any_overlap(&self) -> bool {
+        self.pairs().any(|(r1, r2)| r1.overlaps(r2))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Coverage {
+    pub max: usize,
+    pub at: Vec<Range>, // sections covered by `max` assignments
+}
 
-fn load_pairs(filename: &str) -> Vec<Pair> {
-    load::<String>(filename)
-        .into_iter()
-        .map(|l| {
-            l.split(&['-', ','])
-                .map(|n| n.parse::<u32>().unwrap())
-                .collect()
-        })
-        .collect()
+#[derive(Debug)]
+pub struct Assignments {
+    pub groups: Vec<Group>,
 }
 
-pub fn part1() -> usize {
-    let pairs: Vec<Pair> = load_pairs("data/day4.txt");
-    pairs
-        .into_iter()
-        .filter(|p| (p[0] <= p[2] && p[1] >= p[3]) || (p[0] >= p[2] && p[1] <= p[3]))
-        .count()
+impl FromStr for Assignments {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let groups = s.lines().map(|l| l.parse()).collect::<Result<_, _>>()?;
+        Ok(Assignments { groups })
+    }
 }
 
-pub fn part2() -> usize {
-    let pairs: Vec<Pair> = load_pairs("data/day4.txt");
-    pairs
-        .into_iter()
-        .filter(|p| {
-            (p[2] <= p[0] && p[0] <= p[3])
-                || (p[2] <= p[1] && p[1] <= p[3])
-                || (p[0] <= p[2] && p[2] <= p[1])
-                || (p[0] <= p[3] && p[3] <= p[1])
-        })
-        .count()
+impl Assignments {
+    pub fn load(filename: &str) -> Self {
+        Assignments {
+            groups: load(filename),
+        }
+    }
+
+    // every assignment, in input order
+    pub fn ranges(&self) -> Vec<Range> {
+        self.groups.iter().flat_map(|g| g.ranges.clone()).collect()
+    }
+
+    // sweep line over the start and (one past the) end of every assignment
+    pub fn max_coverage(&self) -> Coverage {
+        let mut events: Vec<(u32, i32)> = self
+            .ranges()
+            .iter()
+            .flat_map(|r| [(r.from, 1), (r.to + 1, -1)])
+            .collect();
+        events.sort();
+        let mut coverage = Coverage { max: 0, at: vec![] };
+        let mut count = 0;
+        for (i, &(x, delta)) in events.iter().enumerate() {
+            count += delta;
+            // only look at the count once all events at `x` are in
+            if events.get(i + 1).is_some_and(|&(next, _)| next == x) {
+                continue;
+            }
+            let count = count as usize;
+            if count > 0 && count >= coverage.max {
+                if count > coverage.max {
+                    coverage = Coverage {
+                        max: count,
+                        at: vec![],
+                    };
+                }
+                // runs until the next event
+                let to = events[i + 1].0 - 1;
+                coverage.at.push(Range { from: x, to });
+            }
+        }
+        coverage
+    }
+
+    // sections nobody is assigned to, between the lowest and highest assigned one
+    pub fn gaps(&self) -> Vec<Range> {
+        let mut ranges = self.ranges();
+        ranges.sort();
+        let mut gaps = vec![];
+        let mut next = match ranges.first() {
+            None => return gaps,
+            Some(r) => r.from,
+        };
+        for r in ranges {
+            if r.from > next {
+                gaps.push(Range {
+                    from: next,
+                    to: r.from - 1,
+                });
+            }
+            next = next.max(r.to + 1);
+        }
+        gaps
+    }
+
+    // for every assignment (numbered as in `ranges`) the other ones it fully contains;
+    // identical assignments contain each other
+    pub fn containment(&self) -> Vec<Vec<usize>> {
+        let ranges = self.ranges();
+        ranges
+            .iter()
+            .enumerate()
+            .map(|(i, r1)| {
+                (0..ranges.len())
+                    .filter(|&j| i != j && r1.contains(&ranges[j]))
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn count(&self, f: impl Fn(&Group) -> bool) -> usize {
+        self.groups.iter().filter(|g| f(g)).count()
+    }
+}
+
+pub fn part1(filename: &str) -> usize {
+    Assignments::load(filename).count(Group::any_contains)
+}
+
+pub fn part2(filename: &str) -> usize {
+    Assignments::load(filename).count(Group::any_overlap)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_assignments() {
+        let a: Assignments = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8,14-15"
+            .parse()
+            .unwrap();
+        assert_eq!(a.count(Group::any_contains), 2);
+        assert_eq!(a.count(Group::any_overlap), 4);
+        let r = |from, to| Range { from, to };
+        assert_eq!(
+            a.max_coverage(),
+            Coverage {
+                max: 8,
+                at: vec![r(6, 6)]
+            }
+        );
+        assert_eq!(a.gaps(), vec![r(10, 13)]);
+        let containment = a.containment();
+        // 2-8 holds most of the others
+        assert_eq!(containment[6], vec![0, 1, 2, 3, 4, 7, 8, 9, 10, 11]);
+        assert_eq!(containment[9], vec![3, 8]);
+        assert!(containment[12].is_empty());
+        assert_eq!("1-2,3".parse::<Group>(), Err(()));
+    }
+
     #[test]
     fn test_part1() {
-        let count = part1();
+        let count = part1("data/day4.txt");
         println!("Fully contains count {}", count);
         assert_eq!(count, 562);
     }
 
     #[test]
     fn test_part2() {
-        let count = part2();
+        let count = part2("data/day4.txt");
         println!("Overlap count {}", count);
         assert_eq!(count, 924);
     }
@@ -1,104 +1,515 @@
-use std::str::FromStr;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
-use crate::util::load;
+use crate::{
+    context::{Context, Outcome},
+    util::load,
+};
 
-enum State {
-    STACKS,
-    MOVES,
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    // the drawing does not end in ` 1   2   3 ...`
+    Footer,
+    // crate drawn beyond the last numbered stack
+    Crate { line: usize, stack: usize },
+    Move(String),
 }
 
-type Crates = [Vec<char>; 9];
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Footer => write!(f, "missing or invalid stack numbers below the drawing"),
+            Self::Crate { line, stack } => {
+                write!(
+                    f,
+                    "line {}: crate on stack {} which is not numbered",
+                    line, stack
+                )
+            }
+            Self::Move(m) => write!(f, "invalid move {:?}", m),
+        }
+    }
+}
+
+// stacks from bottom to top
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Crates {
+    pub stacks: Vec<Vec<char>>,
+}
+
+// the puzzle's drawing, down to and including the numbered footer
+impl FromStr for Crates {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().collect();
+        let (footer, drawing) = lines.split_last().ok_or(ParseError::Footer)?;
+        let numbers: Vec<&str> = footer.split_whitespace().collect();
+        if numbers.is_empty() || (1..).zip(&numbers).any(|(i, n)| n.parse() != Ok(i)) {
+            return Err(ParseError::Footer);
+        }
+        let mut stacks = vec![vec![]; numbers.len()];
+        for (l, line) in drawing.iter().enumerate().rev() {
+            // crate labels sit at every 4th position, lines may be cut short
+            for (stack, c) in line.chars().skip(1).step_by(4).enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+                stacks
+                    .get_mut(stack)
+                    .ok_or(ParseError::Crate {
+                        line: l + 1,
+                        stack: stack + 1,
+                    })?
+                    .push(c);
+            }
+        }
+        Ok(Crates { stacks })
+    }
+}
 
-#[derive(Debug)]
-struct Move {
-    num: usize,
-    from: usize,
-    to: usize,
+impl Display for Crates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let height = self.stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let cells: Vec<String> = self
+                .stacks
+                .iter()
+                .map(|s| match s.get(level) {
+                    Some(c) => format!("[{}]", c),
+                    None => "   ".to_owned(),
+                })
+                .collect();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+        let footer: Vec<String> = (1..=self.stacks.len())
+            .map(|i| format!("{:^3}", i))
+            .collect();
+        writeln!(f, "{}", footer.join(" "))
+    }
+}
+
+impl Crates {
+    // top crate of every stack, empty stacks are left out
+    pub fn tops(&self) -> String {
+        self.stacks.iter().filter_map(|s| s.last()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub num: usize,
+    pub from: usize,
+    pub to: usize,
 }
 
 impl FromStr for Move {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseError::Move(s.to_owned());
         let tokens: Vec<&str> = s.split_whitespace().collect();
-        Ok(Move {
-            num: tokens[1].parse().unwrap(),
-            from: tokens[3].parse::<usize>().unwrap() - 1,
-            to: tokens[5].parse::<usize>().unwrap() - 1,
-        })
+        match tokens[..] {
+            ["move", num, "from", from, "to", to] => {
+                let stack = |t: &str| t.parse::<usize>().ok().filter(|&n| n > 0).ok_or_else(err);
+                Ok(Move {
+                    num: num.parse().map_err(|_| err())?,
+                    from: stack(from)? - 1,
+                    to: stack(to)? - 1,
+                })
+            }
+            _ => Err(err()),
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.num,
+            self.from + 1,
+            self.to + 1
+        )
     }
 }
 
-fn load_and_parse() -> (Crates, Vec<Move>) {
-    fn add_crates(crates: &mut Crates, line: &[u8]) {
-        for i in 0..9 {
-            let idx = 4 * i + 1;
-            let c = line[idx];
-            if idx < line.len() && c >= b'A' && c <= b'Z' {
-                crates[i].insert(0, c as char);
+// the puzzle input for these crates and moves
+pub fn puzzle_input(crates: &Crates, moves: &[Move]) -> String {
+    let mut s = format!("{}\n", crates);
+    for m in moves {
+        s += &format!("{}\n", m);
+    }
+    s
+}
+
+pub fn load_and_parse(filename: &str) -> Result<(Crates, Vec<Move>), ParseError> {
+    let lines: Vec<String> = load(filename);
+    let split = lines
+        .iter()
+        .position(|l| l.is_empty())
+        .unwrap_or(lines.len());
+    let crates = lines[..split].join("\n").parse()?;
+    let moves = lines[split..]
+        .iter()
+        .filter(|l| !l.is_empty())
+        .map(|l| l.parse())
+        .collect::<Result<_, _>>()?;
+    Ok((crates, moves))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoveError {
+    NoStack(usize),
+    NotEnoughCrates { stack: usize, have: usize },
+    TooHigh { stack: usize, limit: usize },
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoStack(s) => write!(f, "there is no stack {}", s),
+            Self::NotEnoughCrates { stack, have } => {
+                write!(f, "stack {} only has {} crates", stack, have)
+            }
+            Self::TooHigh { stack, limit } => {
+                write!(f, "stack {} cannot hold more than {} crates", stack, limit)
             }
         }
     }
+}
+
+pub trait Crane {
+    // crates picked up at once, out of the `left` still to move
+    fn lift(&self, left: usize) -> usize;
+
+    // most crates the stack (counting from 0) can hold
+    fn max_height(&self, _stack: usize) -> Option<usize> {
+        None
+    }
 
-    let lines: Vec<String> = load("data/day5.txt");
-    let mut crates: Crates = Default::default();
-    let mut moves: Vec<Move> = Vec::new();
-    let mut state = State::STACKS;
-    for line in lines.into_iter() {
-        if line.len() == 0 {
-            state = State::MOVES;
-            continue;
+    // moves the crates lift by lift, or leaves them untouched if the move is illegal
+    fn apply(&self, crates: &mut Crates, m: &Move) -> Result<(), MoveError> {
+        for s in [m.from, m.to] {
+            if s >= crates.stacks.len() {
+                return Err(MoveError::NoStack(s + 1));
+            }
         }
-        match state {
-            State::STACKS => add_crates(&mut crates, line.as_bytes()),
-            State::MOVES => moves.push(line.parse().unwrap()),
+        let have = crates.stacks[m.from].len();
+        if have < m.num {
+            return Err(MoveError::NotEnoughCrates {
+                stack: m.from + 1,
+                have,
+            });
         }
+        if let Some(limit) = self.max_height(m.to) {
+            if m.from != m.to && crates.stacks[m.to].len() + m.num > limit {
+                return Err(MoveError::TooHigh {
+                    stack: m.to + 1,
+                    limit,
+                });
+            }
+        }
+        let mut left = m.num;
+        while left > 0 {
+            let lift = self.lift(left).clamp(1, left);
+            let l = crates.stacks[m.from].len();
+            let c = crates.stacks[m.from].split_off(l - lift);
+            crates.stacks[m.to].extend_from_slice(&c);
+            left -= lift;
+        }
+        Ok(())
+    }
+}
+
+// one crate at a time
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn lift(&self, _left: usize) -> usize {
+        1
+    }
+}
+
+// everything at once
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn lift(&self, left: usize) -> usize {
+        left
     }
-    (crates, moves)
 }
 
-fn top_crates(crates: &mut Crates) -> String {
-    crates
-        .into_iter()
-        .map(|stack| stack.pop().unwrap())
-        .collect()
+// takes several trips if the crane would lift more than `max` crates
+pub struct LiftLimit<C> {
+    pub crane: C,
+    pub max: usize,
 }
 
-pub fn part1() -> String {
-    let (mut crates, moves) = load_and_parse();
-    for m in moves.into_iter() {
-        for _ in 0..m.num {
-            let c = crates[m.from].pop().unwrap();
-            crates[m.to].push(c);
+impl<C: Crane> Crane for LiftLimit<C> {
+    fn lift(&self, left: usize) -> usize {
+        self.crane.lift(left).min(self.max)
+    }
+
+    fn max_height(&self, stack: usize) -> Option<usize> {
+        self.crane.max_height(stack)
+    }
+}
+
+// stacks can only get so high, stacks without a limit are unbounded
+pub struct HeightLimit<C> {
+    pub crane: C,
+    pub limits: Vec<usize>,
+}
+
+impl<C: Crane> Crane for HeightLimit<C> {
+    fn lift(&self, left: usize) -> usize {
+        self.crane.lift(left)
+    }
+
+    fn max_height(&self, stack: usize) -> Option<usize> {
+        let own = self.limits.get(stack).copied();
+        match (own, self.crane.max_height(stack)) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+// runs the moves, pushing the configuration after every move to `trace`; stops at
+// the first illegal move, returning its index
+pub fn simulate(
+    crane: &impl Crane,
+    crates: &mut Crates,
+    moves: &[Move],
+    mut trace: Option<&mut Vec<Crates>>,
+) -> Result<(), (usize, MoveError)> {
+    for (i, m) in moves.iter().enumerate() {
+        crane.apply(crates, m).map_err(|e| (i, e))?;
+        if let Some(trace) = trace.as_mut() {
+            trace.push(crates.clone());
+        }
+    }
+    Ok(())
+}
+
+pub enum Goal {
+    Tops(String),
+    Layout(Crates),
+}
+
+impl Goal {
+    fn reached(&self, crates: &Crates) -> bool {
+        match self {
+            Goal::Tops(tops) => crates.tops() == *tops,
+            Goal::Layout(layout) => crates == layout,
+        }
+    }
+}
+
+// breadth first search for the fewest moves reaching the goal, `None` if it cannot
+// be reached at all
+pub fn plan(
+    crane: &impl Crane,
+    start: &Crates,
+    goal: &Goal,
+    ctx: &Context,
+) -> Outcome<Option<Vec<Move>>> {
+    // every configuration seen, with the one it came from and how
+    let mut states: Vec<(Crates, Option<(usize, Move)>)> = vec![(start.clone(), None)];
+    let mut seen = HashSet::from([start.clone()]);
+    let mut q = VecDeque::from([0]);
+    let mut scanned = 0;
+    while let Some(i) = q.pop_front() {
+        scanned += 1;
+        if ctx.tick(scanned, None) {
+            return Outcome::Interrupted(None);
+        }
+        if goal.reached(&states[i].0) {
+            let mut moves = vec![];
+            let mut at = i;
+            while let Some((prev, m)) = states[at].1 {
+                moves.push(m);
+                at = prev;
+            }
+            moves.reverse();
+            return Outcome::Complete(Some(moves));
+        }
+        let n = states[i].0.stacks.len();
+        for from in 0..n {
+            for to in (0..n).filter(|&to| to != from) {
+                for num in 1..=states[i].0.stacks[from].len() {
+                    let m = Move { num, from, to };
+                    let mut next = states[i].0.clone();
+                    if crane.apply(&mut next, &m).is_ok() && seen.insert(next.clone()) {
+                        q.push_back(states.len());
+                        states.push((next, Some((i, m))));
+                    }
+                }
+            }
         }
     }
-    top_crates(&mut crates)
+    Outcome::Complete(None)
+}
+
+// what can go wrong between reading the puzzle and the crates on top
+#[derive(Debug, PartialEq, Eq)]
+pub enum InputError {
+    Parse(ParseError),
+    Move { index: usize, err: MoveError },
 }
 
-pub fn part2() -> String {
-    let (mut crates, moves) = load_and_parse();
-    for m in moves.into_iter() {
-        let l = crates[m.from].len();
-        let c = crates[m.from].split_off(l - m.num);
-        crates[m.to].extend_from_slice(&c);
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Move { index, err } => write!(f, "move {}: {}", index + 1, err),
+        }
     }
-    top_crates(&mut crates)
+}
+
+fn top_crates(filename: &str, crane: &impl Crane) -> Result<String, InputError> {
+    let (mut crates, moves) = load_and_parse(filename).map_err(InputError::Parse)?;
+    simulate(crane, &mut crates, &moves, None)
+        .map_err(|(index, err)| InputError::Move { index, err })?;
+    Ok(crates.tops())
+}
+
+pub fn part1(filename: &str) -> Result<String, InputError> {
+    top_crates(filename, &CrateMover9000)
+}
+
+pub fn part2(filename: &str) -> Result<String, InputError> {
+    top_crates(filename, &CrateMover9001)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::TempInput;
+
+    #[test]
+    fn test_drawing() {
+        let input = std::fs::read_to_string("data/day5.txt").unwrap();
+        let drawing = &input[..input.find("\n\n").unwrap() + 1];
+        let crates: Crates = drawing.parse().unwrap();
+        assert_eq!(crates.stacks.len(), 9);
+        assert_eq!(crates.to_string(), drawing);
+
+        // ragged lines, other labels and more than 9 stacks
+        let crates: Crates = "[x]\n[1] [%]                                     [Z]\n 1   2   3   4   5   6   7   8   9   10  11  12 "
+            .parse()
+            .unwrap();
+        assert_eq!(crates.stacks.len(), 12);
+        assert_eq!(crates.tops(), "x%Z");
+        assert_eq!(crates.to_string().parse(), Ok(crates));
+
+        assert_eq!("[A]\n 2 ".parse::<Crates>(), Err(ParseError::Footer));
+        assert_eq!(
+            "    [A]\n 1 ".parse::<Crates>(),
+            Err(ParseError::Crate { line: 1, stack: 2 })
+        );
+        assert!("move 1 from 0 to 2".parse::<Move>().is_err());
+    }
+
+    #[test]
+    fn test_cranes() {
+        let crates: Crates = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n"
+            .parse()
+            .unwrap();
+        let m = |num, from, to| Move { num, from, to };
+        let moves = [m(1, 1, 0), m(3, 0, 2), m(2, 1, 0), m(1, 0, 1)];
+        let mut c = crates.clone();
+        simulate(&CrateMover9000, &mut c, &moves, None).unwrap();
+        assert_eq!(c.tops(), "CMZ");
+        let mut c = crates.clone();
+        simulate(&CrateMover9001, &mut c, &moves, None).unwrap();
+        assert_eq!(c.tops(), "MCD");
+        // 3 crates in trips of 2 and 1
+        let crane = LiftLimit {
+            crane: CrateMover9001,
+            max: 2,
+        };
+        let mut c = crates.clone();
+        crane.apply(&mut c, &m(3, 1, 2)).unwrap();
+        assert_eq!(c.stacks[2], vec!['P', 'C', 'D', 'M']);
+
+        let crane = HeightLimit {
+            crane: CrateMover9001,
+            limits: vec![3, 3, 3],
+        };
+        let mut c = crates.clone();
+        let mut trace = vec![];
+        let error = simulate(&crane, &mut c, &moves, Some(&mut trace));
+        assert_eq!(error, Err((1, MoveError::TooHigh { stack: 3, limit: 3 })));
+        assert_eq!(trace.len(), 1);
+        assert_eq!(trace[0], c);
+        assert_eq!(
+            CrateMover9000.apply(&mut c, &m(4, 2, 0)),
+            Err(MoveError::NotEnoughCrates { stack: 3, have: 1 })
+        );
+        assert_eq!(
+            CrateMover9000.apply(&mut c, &m(1, 2, 3)),
+            Err(MoveError::NoStack(4))
+        );
+        assert_eq!(c, trace[0]);
+    }
+
+    #[test]
+    fn test_plan() {
+        let crates: Crates = "[A]        \n[B] [C] [D]\n 1   2   3 \n".parse().unwrap();
+        let ctx = Context::new();
+        let goal = Goal::Tops("CAB".to_owned());
+        let moves = plan(&CrateMover9000, &crates, &goal, &ctx)
+            .unwrap()
+            .unwrap();
+        // C has to wait for A to get out of the way
+        assert_eq!(moves.len(), 4);
+
+        // the plan is valid puzzle input again
+        let input = TempInput::new(puzzle_input(&crates, &moves)).unwrap();
+        let (mut c, moves) = load_and_parse(input.filename()).unwrap();
+        simulate(&CrateMover9000, &mut c, &moves, None).unwrap();
+        assert_eq!(c.tops(), "CAB");
+
+        // the 9001 moves both at once, the 9000 has to reverse them twice
+        let layout: Crates = "    [A]    \n    [B]    \n    [C] [D]\n 1   2   3 \n"
+            .parse()
+            .unwrap();
+        let goal = Goal::Layout(layout);
+        let moves = plan(&CrateMover9001, &crates, &goal, &ctx)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            moves.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            vec!["move 2 from 1 to 2"]
+        );
+        let moves = plan(&CrateMover9000, &crates, &goal, &ctx)
+            .unwrap()
+            .unwrap();
+        assert_eq!(moves.len(), 2);
+
+        let goal = Goal::Tops("XYZ".to_owned());
+        assert_eq!(
+            plan(&CrateMover9000, &crates, &goal, &ctx),
+            Outcome::Complete(None)
+        );
+    }
 
     #[test]
     fn test_part1() {
-        let top = part1();
+        let top = part1("data/day5.txt").unwrap();
         println!("Top crates are {}", top);
         assert_eq!(top, "VPCDMSLWJ");
     }
 
     #[test]
     fn test_part2() {
-        let top = part2();
+        let top = part2("data/day5.txt").unwrap();
         println!("Top crates are {}", top);
         assert_eq!(top, "TPWCGNCCG");
     }
@@ -1,5 +1,7 @@
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read},
+};
 
 use crate::util::load;
 
@@ -7,37 +9,163 @@ pub fn input() -> Vec<String> {
     load("data/day6.txt")
 }
 
-fn find_marker_end(s: &[u8], sz: usize) -> usize {
-    let mut h: HashMap<u8, usize> = HashMap::new();
-    for i in 0..s.len() {
-        if i > sz - 1 {
-            let e = h.entry(s[i - sz]).and_modify(|cnt| *cnt -= 1);
-            if let Entry::Occupied(o) = e {
-                if o.get() == &0 {
-                    o.remove_entry();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+    pub size: usize,
+    pub end: usize, // bytes read up to and including the marker
+}
+
+// Tracks the last bytes for several window sizes at once. Every window keeps the xor
+// of its bytes as a 256 bit mask: a byte seen twice cancels out, so the window holds
+// distinct bytes only if all its bits are still set.
+pub struct Detector {
+    sizes: Vec<usize>,
+    masks: Vec<[u64; 4]>,
+    ring: Vec<u8>,
+    pos: usize,
+}
+
+fn toggle(mask: &mut [u64; 4], b: u8) {
+    mask[(b >> 6) as usize] ^= 1 << (b & 63);
+}
+
+impl Detector {
+    // a size given twice is only tracked, and reported, once
+    pub fn new(sizes: &[usize]) -> Self {
+        assert!(!sizes.is_empty(), "no window sizes");
+        assert!(
+            sizes.iter().all(|s| (1..=256).contains(s)),
+            "window sizes must be 1 to 256"
+        );
+        let mut sizes = sizes.to_vec();
+        sizes.sort_unstable();
+        sizes.dedup();
+        Detector {
+            masks: vec![[0; 4]; sizes.len()],
+            ring: vec![0; *sizes.last().unwrap()],
+            sizes,
+            pos: 0,
+        }
+    }
+
+    // feeds the next byte, `found` gets every window that ends in a marker
+    pub fn push(&mut self, b: u8, mut found: impl FnMut(Marker)) {
+        let len = self.ring.len();
+        for (&size, mask) in self.sizes.iter().zip(self.masks.iter_mut()) {
+            toggle(mask, b);
+            if self.pos >= size {
+                toggle(mask, self.ring[(self.pos - size) % len]);
+            }
+            let distinct: u32 = mask.iter().map(|m| m.count_ones()).sum();
+            if distinct as usize == size {
+                found(Marker {
+                    size,
+                    end: self.pos + 1,
+                });
+            }
+        }
+        self.ring[self.pos % len] = b;
+        self.pos += 1;
+    }
+}
+
+// every marker in the stream, in order, smaller windows first for markers ending together
+pub struct Markers<R> {
+    reader: R,
+    buf: Box<[u8]>,
+    len: usize,
+    at: usize,
+    detector: Detector,
+    pending: VecDeque<Marker>,
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = io::Result<Marker>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(m) = self.pending.pop_front() {
+                return Some(Ok(m));
+            }
+            if self.at == self.len {
+                match self.reader.read(&mut self.buf) {
+                    Ok(0) => return None,
+                    Ok(n) => (self.len, self.at) = (n, 0),
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Some(Err(e)),
                 }
             }
+            let b = self.buf[self.at];
+            self.at += 1;
+            let pending = &mut self.pending;
+            self.detector.push(b, |m| pending.push_back(m));
+        }
+    }
+}
+
+pub fn markers<R: Read>(reader: R, sizes: &[usize]) -> Markers<R> {
+    Markers {
+        reader,
+        buf: vec![0; 1 << 16].into_boxed_slice(),
+        len: 0,
+        at: 0,
+        detector: Detector::new(sizes),
+        pending: VecDeque::new(),
+    }
+}
+
+// end of the first marker of each size, in one pass over the stream
+pub fn first_markers(reader: impl Read, sizes: &[usize]) -> io::Result<Vec<Option<usize>>> {
+    let mut first = vec![None; sizes.len()];
+    if sizes.is_empty() {
+        return Ok(first);
+    }
+    for m in markers(reader, sizes) {
+        let m = m?;
+        for (f, _) in first.iter_mut().zip(sizes).filter(|(_, &s)| s == m.size) {
+            *f = f.or(Some(m.end));
         }
-        h.entry(s[i]).and_modify(|cnt| *cnt += 1).or_insert(1);
-        if h.len() == sz {
-            return i;
+        if first.iter().all(|f| f.is_some()) {
+            break;
         }
     }
-    0
+    Ok(first)
+}
+
+pub fn find_marker_end(reader: impl Read, size: usize) -> io::Result<Option<usize>> {
+    Ok(first_markers(reader, &[size])?[0])
 }
 
 pub fn part1(stream: &str) -> usize {
-    find_marker_end(stream.as_bytes(), 4) + 1 // 1-based indexing
+    find_marker_end(stream.as_bytes(), 4).unwrap().unwrap()
 }
 
 pub fn part2(stream: &str) -> usize {
-    find_marker_end(stream.as_bytes(), 14) + 1 // 1-based indexing
+    find_marker_end(stream.as_bytes(), 14).unwrap().unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_markers() {
+        let found: Vec<Marker> = markers("abcabbxyz".as_bytes(), &[3, 4])
+            .map(|m| m.unwrap())
+            .collect();
+        let m = |size, end| Marker { size, end };
+        assert_eq!(
+            found,
+            vec![m(3, 3), m(3, 4), m(3, 5), m(3, 8), m(3, 9), m(4, 9)]
+        );
+        assert_eq!(find_marker_end("aaaa".as_bytes(), 2).unwrap(), None);
+
+        let file = std::fs::File::open("data/day6.txt").unwrap();
+        let first = first_markers(file, &[14, 4, 14]).unwrap();
+        assert_eq!(first, vec![Some(3605), Some(1275), Some(3605)]);
+        assert_eq!(first_markers("abcd".as_bytes(), &[]).unwrap(), vec![]);
+    }
+
     #[test]
     fn test_part1() {
         assert_eq!(part1("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), 7);
@@ -1,133 +1,752 @@
-use std::{cell::Cell, collections::HashMap};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
 
-use crate::util::load;
+use crate::{
+    context::{Context, Outcome},
+    util::{json_string, load},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShellError {
+    UnknownCommand { line: usize, command: String },
+    NotADirectory { line: usize, path: String },
+    // listed a directory where a file is, or the other way round
+    Conflict { line: usize, name: String },
+    // output while no `ls` is running, or output that is not a listing
+    UnexpectedOutput { line: usize },
+}
+
+impl Display for ShellError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownCommand { line, command } => {
+                write!(f, "line {}: unknown command {:?}", line, command)
+            }
+            Self::NotADirectory { line, path } => {
+                write!(f, "line {}: {} is not a directory", line, path)
+            }
+            Self::Conflict { line, name } => {
+                write!(
+                    f,
+                    "line {}: {} listed as both file and directory",
+                    line, name
+                )
+            }
+            Self::UnexpectedOutput { line } => write!(f, "line {}: unexpected output", line),
+        }
+    }
+}
 
 #[derive(Debug)]
-enum File {
-    Plain(usize),
-    Folder(HashMap<String, File>, Cell<usize>),
-}
-
-fn add_to_folder(folder: &mut File, name: String, entry: File) {
-    match folder {
-        File::Plain(_) => panic!("cannot add to plain file"),
-        File::Folder(ref mut contents, _) => drop(contents.insert(name, entry)),
-    }
-}
-
-fn parse_lines(it: &mut impl Iterator<Item = String>, folder: &mut File) {
-    loop {
-        match &it.next() {
-            None => return,
-            Some(line) => {
-                let token: Vec<&str> = line.split(' ').collect();
-                match token[0] {
-                    "$" => match token[1] {
-                        "cd" => match token[2] {
-                            ".." => return,
-                            name => {
-                                if let File::Folder(ref mut map, _) = folder {
-                                    if let Some(f) = map.get_mut(name) {
-                                        parse_lines(it, f);
-                                    }
-                                }
-                            }
-                        },
-                        "ls" => (), // nop
-                        _ => panic!("unexpected cmd"),
-                    },
-                    "dir" => add_to_folder(
-                        folder,
-                        token[1].to_owned(),
-                        File::Folder(HashMap::new(), Cell::new(0)),
-                    ),
-                    sz => add_to_folder(
-                        folder,
-                        token[1].to_owned(),
-                        File::Plain(sz.parse::<usize>().unwrap()),
-                    ),
+enum Kind {
+    File,
+    Dir(BTreeMap<String, usize>),
+}
+
+#[derive(Debug)]
+struct Node {
+    name: String,
+    parent: Option<usize>,
+    kind: Kind,
+    size: usize, // total size of everything below for directories
+}
+
+// Files and directories live in one list and refer to each other by index, the
+// root directory is 0.
+#[derive(Debug)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        FileSystem {
+            nodes: vec![Node {
+                name: String::new(),
+                parent: None,
+                kind: Kind::Dir(BTreeMap::new()),
+                size: 0,
+            }],
+        }
+    }
+}
+
+impl FileSystem {
+    pub const ROOT: usize = 0;
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.nodes[id].name
+    }
+
+    pub fn parent(&self, id: usize) -> Option<usize> {
+        self.nodes[id].parent
+    }
+
+    pub fn is_dir(&self, id: usize) -> bool {
+        matches!(self.nodes[id].kind, Kind::Dir(_))
+    }
+
+    pub fn size(&self, id: usize) -> usize {
+        self.nodes[id].size
+    }
+
+    // sorted by name, nothing for files
+    pub fn children(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        match &self.nodes[id].kind {
+            Kind::Dir(children) => Some(children.values().copied()),
+            Kind::File => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    pub fn child(&self, id: usize, name: &str) -> Option<usize> {
+        match &self.nodes[id].kind {
+            Kind::Dir(children) => children.get(name).copied(),
+            Kind::File => None,
+        }
+    }
+
+    pub fn path(&self, id: usize) -> String {
+        match self.parent(id) {
+            None => "/".to_owned(),
+            Some(Self::ROOT) => format!("/{}", self.name(id)),
+            Some(parent) => format!("{}/{}", self.path(parent), self.name(id)),
+        }
+    }
+
+    // absolute path, or relative to `from`; `.` and `..` are fine
+    pub fn resolve(&self, from: usize, path: &str) -> Option<usize> {
+        let mut at = if path.starts_with('/') {
+            Self::ROOT
+        } else {
+            from
+        };
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            at = match part {
+                "." => at,
+                ".." => self.parent(at).unwrap_or(Self::ROOT),
+                name => self.child(at, name)?,
+            };
+        }
+        Some(at)
+    }
+
+    pub fn lookup(&self, path: &str) -> Option<usize> {
+        self.resolve(Self::ROOT, path)
+    }
+
+    // depth first, every directory before its contents: (depth, id)
+    pub fn walk(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut stack = vec![(0, Self::ROOT)];
+        std::iter::from_fn(move || {
+            let (depth, id) = stack.pop()?;
+            let children: Vec<usize> = self.children(id).collect();
+            stack.extend(children.into_iter().rev().map(|c| (depth + 1, c)));
+            Some((depth, id))
+        })
+    }
+
+    pub fn dirs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.walk().map(|(_, id)| id).filter(|&id| self.is_dir(id))
+    }
+
+    // the existing entry if there is one already, `None` if it is of the other kind
+    fn add(&mut self, dir: usize, name: &str, file_size: Option<usize>) -> Option<usize> {
+        if let Some(id) = self.child(dir, name) {
+            return match (&self.nodes[id].kind, file_size) {
+                (Kind::Dir(_), None) => Some(id),
+                (Kind::File, Some(size)) => {
+                    // listed again, maybe with a new size
+                    let old = self.nodes[id].size;
+                    self.grow(id, size as isize - old as isize);
+                    Some(id)
                 }
-            }
+                _ => None,
+            };
+        }
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_owned(),
+            parent: Some(dir),
+            kind: match file_size {
+                None => Kind::Dir(BTreeMap::new()),
+                Some(_) => Kind::File,
+            },
+            size: 0,
+        });
+        if let Kind::Dir(children) = &mut self.nodes[dir].kind {
+            children.insert(name.to_owned(), id);
+        }
+        self.grow(id, file_size.unwrap_or(0) as isize);
+        Some(id)
+    }
+
+    // keeps the sizes of all directories above up to date
+    fn grow(&mut self, id: usize, delta: isize) {
+        let mut at = Some(id);
+        while let Some(id) = at {
+            self.nodes[id].size = (self.nodes[id].size as isize + delta) as usize;
+            at = self.nodes[id].parent;
         }
     }
 }
 
-fn parse_input() -> File {
-    let lines: Vec<String> = load("data/day7.txt");
-    let mut fs = File::Folder(HashMap::new(), Cell::new(0));
-    parse_lines(&mut lines.into_iter(), &mut fs);
-    fs
+// replays a terminal session of `cd` and `ls` into a file system
+#[derive(Default)]
+pub struct Shell {
+    pub fs: FileSystem,
+    // directories from the root down to the current one
+    pub cwd: Vec<usize>,
+    listing: bool,
 }
 
-fn calculate_size(fs: &File, limit: usize) -> (usize, usize) {
-    let mut total_sz = 0;
-    let mut this_sz = 0;
-    if let File::Folder(contents, cell) = fs {
-        for f in contents.values() {
-            match f {
-                File::Plain(sz) => this_sz += sz,
-                File::Folder(_, _) => {
-                    let (total, sub) = calculate_size(f, limit);
-                    total_sz += total + (if sub <= limit { sub } else { 0 });
-                    this_sz += sub;
+impl Shell {
+    pub fn cwd(&self) -> usize {
+        self.cwd.last().copied().unwrap_or(FileSystem::ROOT)
+    }
+
+    // `cd` into directories not listed yet creates them
+    fn cd(&mut self, line: usize, path: &str) -> Result<(), ShellError> {
+        if path.starts_with('/') {
+            self.cwd.clear();
+        }
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            match part {
+                "." => (),
+                ".." => drop(self.cwd.pop()),
+                name => {
+                    let id = self.fs.add(self.cwd(), name, None).ok_or_else(|| {
+                        ShellError::NotADirectory {
+                            line,
+                            path: path.to_owned(),
+                        }
+                    })?;
+                    self.cwd.push(id);
                 }
             }
         }
-        cell.set(this_sz);
+        Ok(())
+    }
+
+    // one line of the session, counting lines from 1
+    pub fn feed(&mut self, line: usize, s: &str) -> Result<(), ShellError> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        match tokens[..] {
+            ["$", ref command @ ..] => {
+                self.listing = false;
+                match command {
+                    ["cd", path] => self.cd(line, path)?,
+                    ["ls"] => self.listing = true,
+                    _ => {
+                        return Err(ShellError::UnknownCommand {
+                            line,
+                            command: command.join(" "),
+                        })
+                    }
+                }
+            }
+            [kind, name] if self.listing => {
+                let size = match kind {
+                    "dir" => None,
+                    size => Some(
+                        size.parse()
+                            .map_err(|_| ShellError::UnexpectedOutput { line })?,
+                    ),
+                };
+                self.fs
+                    .add(self.cwd(), name, size)
+                    .ok_or_else(|| ShellError::Conflict {
+                        line,
+                        name: name.to_owned(),
+                    })?;
+            }
+            [] => (),
+            _ => return Err(ShellError::UnexpectedOutput { line }),
+        }
+        Ok(())
+    }
+
+    pub fn run<S: AsRef<str>>(lines: &[S]) -> Result<FileSystem, ShellError> {
+        let mut shell = Shell::default();
+        for (i, l) in lines.iter().enumerate() {
+            shell.feed(i + 1, l.as_ref())?;
+        }
+        Ok(shell.fs)
+    }
+}
+
+pub fn load_fs(filename: &str) -> Result<FileSystem, ShellError> {
+    Shell::run(&load::<String>(filename))
+}
+
+// `*` matches any run of characters, everything else itself
+fn glob(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((head, rest)) => {
+            name.starts_with(head)
+                && (0..=name.len() - head.len()).any(|i| {
+                    name.is_char_boundary(head.len() + i) && glob(rest, &name[head.len() + i..])
+                })
+        }
     }
-    (total_sz, this_sz)
 }
 
-fn find_to_delete(
-    contents: &HashMap<String, File>,
-    at_least: usize,
-    mut current_sz: usize,
-) -> usize {
-    for f in contents.values() {
-        if let File::Folder(sub_contents, cell) = f {
-            let sz = cell.get();
-            if at_least <= sz && sz < current_sz {
-                current_sz = sz;
+// what `find` looks for; everything left at `None` matches
+#[derive(Debug, Default, Clone)]
+pub struct Find {
+    pub name: Option<String>, // glob on the name, not the path
+    pub dirs: Option<bool>,   // only directories, or only files
+    pub min_size: Option<usize>,
+    pub max_size: Option<usize>,
+}
+
+impl Find {
+    pub fn matches(&self, fs: &FileSystem, id: usize) -> bool {
+        let size = fs.size(id);
+        self.name.as_ref().is_none_or(|n| glob(n, fs.name(id)))
+            && self.dirs.is_none_or(|d| d == fs.is_dir(id))
+            && self.min_size.is_none_or(|min| size >= min)
+            && self.max_size.is_none_or(|max| size <= max)
+    }
+}
+
+impl FileSystem {
+    // one (size, path) line per directory, contents before the directory itself as `du` does
+    pub fn du(&self) -> Vec<(usize, String)> {
+        fn visit(fs: &FileSystem, id: usize, out: &mut Vec<(usize, String)>) {
+            for child in fs.children(id).filter(|&c| fs.is_dir(c)) {
+                visit(fs, child, out);
             }
-            current_sz = find_to_delete(sub_contents, at_least, current_sz);
+            out.push((fs.size(id), fs.path(id)));
         }
+        let mut out = vec![];
+        visit(self, Self::ROOT, &mut out);
+        out
+    }
+
+    // indented listing in the style of the puzzle text
+    pub fn tree(&self) -> String {
+        self.walk()
+            .map(|(depth, id)| {
+                let name = if id == Self::ROOT { "/" } else { self.name(id) };
+                let kind = if self.is_dir(id) { "dir" } else { "file" };
+                format!(
+                    "{}- {} ({}, size={})\n",
+                    "  ".repeat(depth),
+                    name,
+                    kind,
+                    self.size(id)
+                )
+            })
+            .collect()
+    }
+
+    pub fn find(&self, query: &Find) -> Vec<usize> {
+        self.walk()
+            .map(|(_, id)| id)
+            .filter(|&id| query.matches(self, id))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        self.json(Self::ROOT)
+    }
+
+    fn json(&self, id: usize) -> String {
+        let name = json_string(if id == Self::ROOT { "/" } else { self.name(id) });
+        if !self.is_dir(id) {
+            return format!(
+                "{{\"name\":{},\"type\":\"file\",\"size\":{}}}",
+                name,
+                self.size(id)
+            );
+        }
+        let children: Vec<String> = self.children(id).map(|c| self.json(c)).collect();
+        format!(
+            "{{\"name\":{},\"type\":\"dir\",\"size\":{},\"children\":[{}]}}",
+            name,
+            self.size(id),
+            children.join(",")
+        )
     }
-    current_sz
 }
 
-pub fn part1() -> usize {
-    let sz_limit = 100000;
-    let fs = parse_input();
-    calculate_size(&fs, sz_limit).0
+#[derive(Debug, Clone, Copy)]
+pub struct Disk {
+    pub size: usize,
+    pub needed: usize, // free space the update wants
 }
 
-pub fn part2() -> usize {
-    let sz_limit = 100000;
-    let fs = parse_input();
-    calculate_size(&fs, sz_limit);
-    let disk_sz = 70000000;
-    let free_sz_required = 30000000;
-    match fs {
-        File::Plain(_) => panic!("root folder should not be plain file"),
-        File::Folder(contents, sz) => {
-            let disk_used_sz = sz.get();
-            let min_delete_sz = free_sz_required - (disk_sz - disk_used_sz);
-            find_to_delete(&contents, min_delete_sz, disk_sz)
+impl Default for Disk {
+    fn default() -> Self {
+        Disk {
+            size: 70000000,
+            needed: 30000000,
         }
     }
 }
 
+impl Disk {
+    pub fn free(&self, fs: &FileSystem) -> usize {
+        self.size.saturating_sub(fs.size(FileSystem::ROOT))
+    }
+
+    // how much has to go before the update fits
+    pub fn to_free(&self, fs: &FileSystem) -> usize {
+        self.needed.saturating_sub(self.free(fs))
+    }
+}
+
+pub fn small_dirs_total(fs: &FileSystem, threshold: usize) -> usize {
+    fs.find(&Find {
+        dirs: Some(true),
+        max_size: Some(threshold),
+        ..Find::default()
+    })
+    .into_iter()
+    .map(|d| fs.size(d))
+    .sum()
+}
+
+// the smallest directory freeing enough space
+pub fn find_to_delete(fs: &FileSystem, disk: &Disk) -> Option<usize> {
+    fs.find(&Find {
+        dirs: Some(true),
+        min_size: Some(disk.to_free(fs)),
+        ..Find::default()
+    })
+    .into_iter()
+    .min_by_key(|&d| fs.size(d))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    FewestDirs, // ties go to the fewest bytes freed
+    LeastOver,  // fewest bytes freed beyond the target, ties to the fewest directories
+}
+
+impl Objective {
+    fn key(&self, count: usize, freed: usize) -> (usize, usize) {
+        match self {
+            Self::FewestDirs => (count, freed),
+            Self::LeastOver => (freed, count),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Plan {
+    pub dirs: Vec<usize>,
+    pub freed: usize,
+    pub before: usize, // used space
+    pub after: usize,
+}
+
+impl Plan {
+    pub fn report(&self, fs: &FileSystem, disk: &Disk) -> String {
+        let mut out: String = self
+            .dirs
+            .iter()
+            .map(|&d| format!("rm -r {} # {}\n", fs.path(d), fs.size(d)))
+            .collect();
+        out += &format!(
+            "used {} -> {}, free {} -> {} of {}\n",
+            self.before,
+            self.after,
+            disk.size.saturating_sub(self.before),
+            disk.size.saturating_sub(self.after),
+            disk.size
+        );
+        out
+    }
+}
+
+// directories in walk order, so a subtree is the run from a directory to its `end`
+struct Search<'a> {
+    dirs: Vec<usize>,
+    sizes: Vec<usize>,
+    end: Vec<usize>,
+    allowed: Vec<bool>,
+    reach: Vec<usize>, // most that can be freed from here on
+    target: usize,
+    objective: Objective,
+    ctx: &'a Context<'a>,
+    scanned: u64,
+    interrupted: bool,
+    chosen: Vec<usize>,
+    best: Option<((usize, usize), Vec<usize>)>,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, i: usize, freed: usize) {
+        self.scanned += 1;
+        if self.interrupted || self.ctx.tick(self.scanned, None) {
+            self.interrupted = true;
+            return;
+        }
+        let count = self.chosen.len();
+        if freed >= self.target {
+            let key = self.objective.key(count, freed);
+            if self.best.as_ref().is_none_or(|(best, _)| key < *best) {
+                self.best = Some((key, self.chosen.clone()));
+            }
+            return;
+        }
+        if i == self.dirs.len() || freed + self.reach[i] < self.target {
+            return;
+        }
+        // at least one more directory, and more than freed so far
+        let bound = self.objective.key(count + 1, self.target.max(freed + 1));
+        if self.best.as_ref().is_some_and(|(best, _)| bound >= *best) {
+            return;
+        }
+        if self.allowed[i] {
+            self.chosen.push(i);
+            self.visit(self.end[i], freed + self.sizes[i]);
+            self.chosen.pop();
+        }
+        self.visit(i + 1, freed);
+    }
+}
+
+// directories to remove, none inside another, freeing at least `target` bytes; neither the
+// root nor anything holding a protected entry goes
+pub fn plan_deletion(
+    fs: &FileSystem,
+    target: usize,
+    objective: Objective,
+    protected: &[usize],
+    ctx: &Context,
+) -> Outcome<Option<Plan>> {
+    let walk: Vec<(usize, usize)> = fs.walk().filter(|&(_, id)| fs.is_dir(id)).collect();
+    let n = walk.len();
+    let mut end = vec![n; n];
+    let mut open: Vec<usize> = vec![];
+    for (i, &(depth, _)) in walk.iter().enumerate() {
+        while open.last().is_some_and(|&j| walk[j].0 >= depth) {
+            end[open.pop().unwrap()] = i;
+        }
+        open.push(i);
+    }
+    let mut blocked = vec![false; fs.nodes.len()];
+    blocked[FileSystem::ROOT] = true;
+    for &p in protected {
+        let mut at = Some(p);
+        while let Some(id) = at {
+            blocked[id] = true;
+            at = fs.parent(id);
+        }
+    }
+    let dirs: Vec<usize> = walk.into_iter().map(|(_, id)| id).collect();
+    let sizes: Vec<usize> = dirs.iter().map(|&d| fs.size(d)).collect();
+    let allowed: Vec<bool> = dirs.iter().map(|&d| !blocked[d]).collect();
+    let mut reach = vec![0; n + 1];
+    for i in (0..n).rev() {
+        reach[i] = reach[i + 1];
+        if allowed[i] {
+            reach[i] = reach[i].max(sizes[i] + reach[end[i]]);
+        }
+    }
+    let mut search = Search {
+        dirs,
+        sizes,
+        end,
+        allowed,
+        reach,
+        target,
+        objective,
+        ctx,
+        scanned: 0,
+        interrupted: false,
+        chosen: vec![],
+        best: None,
+    };
+    search.visit(0, 0);
+    let before = fs.size(FileSystem::ROOT);
+    let plan = search.best.map(|((_, _), chosen)| {
+        let dirs: Vec<usize> = chosen.iter().map(|&i| search.dirs[i]).collect();
+        let freed = chosen.iter().map(|&i| search.sizes[i]).sum();
+        Plan {
+            dirs,
+            freed,
+            before,
+            after: before - freed,
+        }
+    });
+    if search.interrupted {
+        Outcome::Interrupted(Some(plan))
+    } else {
+        Outcome::Complete(plan)
+    }
+}
+
+pub fn part1(filename: &str) -> Result<usize, ShellError> {
+    Ok(small_dirs_total(&load_fs(filename)?, 100000))
+}
+
+pub fn part2(filename: &str) -> Result<usize, ShellError> {
+    let fs = load_fs(filename)?;
+    Ok(fs.size(find_to_delete(&fs, &Disk::default()).unwrap()))
+}
+
+#[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell() {
+        let session = [
+            "$ cd /a/b", // not listed yet
+            "$ ls",
+            "10 f",
+            "dir c",
+            "$ cd /",
+            "$ ls",
+            "dir a",
+            "5 g",
+            "$ ls", // again, with g grown
+            "7 g",
+            "$ cd a/b/c/../../b/./c",
+            "$ ls",
+            "100 h",
+            "$ cd ..",
+            "$ cd ..",
+            "$ cd ..",
+            "$ cd ..",
+        ];
+        let fs = Shell::run(&session).unwrap();
+        assert_eq!(fs.size(FileSystem::ROOT), 117);
+        let c = fs.lookup("/a/b/c").unwrap();
+        assert_eq!(fs.path(c), "/a/b/c");
+        assert_eq!(fs.size(fs.lookup("/a").unwrap()), 110);
+        assert_eq!(fs.resolve(c, "../f"), fs.lookup("/a/b/f"));
+        assert_eq!(fs.lookup("/a/x"), None);
+        let walk: Vec<(usize, String)> = fs.walk().map(|(d, id)| (d, fs.path(id))).collect();
+        assert_eq!(
+            walk,
+            [
+                (0, "/"),
+                (1, "/a"),
+                (2, "/a/b"),
+                (3, "/a/b/c"),
+                (4, "/a/b/c/h"),
+                (3, "/a/b/f"),
+                (1, "/g")
+            ]
+            .map(|(d, p)| (d, p.to_owned()))
+        );
+
+        assert_eq!(
+            Shell::run(&["$ cd /", "$ rm -rf *"]).unwrap_err(),
+            ShellError::UnknownCommand {
+                line: 2,
+                command: "rm -rf *".to_owned()
+            }
+        );
+        assert_eq!(
+            Shell::run(&["$ ls", "1 f", "$ cd f"]).unwrap_err(),
+            ShellError::NotADirectory {
+                line: 3,
+                path: "f".to_owned()
+            }
+        );
+        assert_eq!(
+            Shell::run(&["$ cd /", "1 f"]).unwrap_err(),
+            ShellError::UnexpectedOutput { line: 2 }
+        );
+    }
+
+    #[test]
+    fn test_reports() {
+        let session = "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n$ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst\n$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k";
+        let fs = Shell::run(&session.lines().collect::<Vec<_>>()).unwrap();
+        assert_eq!(small_dirs_total(&fs, 100000), 95437);
+        let disk = Disk::default();
+        assert_eq!(disk.to_free(&fs), 8381165);
+        assert_eq!(find_to_delete(&fs, &disk), fs.lookup("/d"));
+        let nested = Shell::run(&["$ cd /a/b/c", "$ cd /d/e"]).unwrap();
+        let du: Vec<String> = nested.du().into_iter().map(|(_, p)| p).collect();
+        assert_eq!(du, ["/a/b/c", "/a/b", "/a", "/d/e", "/d", "/"]);
+        assert_eq!(
+            fs.du(),
+            [
+                (584, "/a/e"),
+                (94853, "/a"),
+                (24933642, "/d"),
+                (48381165, "/")
+            ]
+            .map(|(sz, p)| (sz, p.to_owned()))
+        );
+        assert!(fs.tree().starts_with("- / (dir, size=48381165)\n  - a (dir, size=94853)\n    - e (dir, size=584)\n      - i (file, size=584)\n"));
+        let logs = fs.find(&Find {
+            name: Some("*.l*".to_owned()),
+            min_size: Some(100000),
+            ..Find::default()
+        });
+        assert_eq!(logs, [fs.lookup("/d/d.log").unwrap()]);
+        assert!(fs
+            .to_json()
+            .starts_with(r#"{"name":"/","type":"dir","size":48381165,"children":[{"name":"a","#));
+    }
+
+    #[test]
+    fn test_plan_deletion() {
+        let session = [
+            "$ cd /", "$ ls", "dir a", "dir b", "dir c", "$ cd a", "$ ls", "50 x", "dir d",
+            "$ cd d", "$ ls", "30 y", "$ cd /b", "$ ls", "45 z", "$ cd /c", "$ ls", "20 w",
+            "1 keep",
+        ];
+        let fs = Shell::run(&session).unwrap();
+        let ctx = Context::new();
+        let plan = |target, objective, protected: &[&str]| {
+            let protected: Vec<usize> = protected.iter().map(|p| fs.lookup(p).unwrap()).collect();
+            plan_deletion(&fs, target, objective, &protected, &ctx)
+                .unwrap()
+                .map(|p| p.dirs.iter().map(|&d| fs.path(d)).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            plan(60, Objective::FewestDirs, &[]),
+            Some(vec!["/a".to_owned()])
+        );
+        // d and b give exactly 75
+        assert_eq!(
+            plan(75, Objective::LeastOver, &[]),
+            Some(vec!["/a/d".to_owned(), "/b".to_owned()])
+        );
+        assert_eq!(
+            plan(60, Objective::FewestDirs, &["/a/x"]),
+            Some(vec!["/b".to_owned(), "/c".to_owned()])
+        );
+        assert_eq!(plan(100, Objective::FewestDirs, &["/a/x", "/c/keep"]), None);
+
+        let p = plan_deletion(&fs, 60, Objective::FewestDirs, &[], &ctx)
+            .unwrap()
+            .unwrap();
+        assert_eq!((p.before, p.after), (146, 66));
+        let disk = Disk {
+            size: 200,
+            needed: 100,
+        };
+        assert_eq!(
+            p.report(&fs, &disk),
+            "rm -r /a # 80\nused 146 -> 66, free 54 -> 134 of 200\n"
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let sz = super::part1();
+        let sz = part1("data/day7.txt").unwrap();
         println!("Total size of -100K directories: {}", sz);
         assert_eq!(sz, 1844187);
     }
 
     #[test]
     fn test_part2() {
-        let sz = super::part2();
+        let sz = part2("data/day7.txt").unwrap();
         println!("Freeing up a directory of size: {}", sz);
         assert_eq!(sz, 4978279);
     }
@@ -1,114 +1,290 @@
-use std::{cmp::max, collections::HashSet, iter::repeat};
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
 use crate::util::load;
 
-#[derive(Eq, Hash, PartialEq)]
-struct Coord {
-    x: usize,
-    y: usize,
+// one value per tree, row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<T>,
 }
 
-fn input() -> Vec<Vec<u8>> {
-    let lines: Vec<String> = load("data/day8.txt");
-    lines
-        .into_iter()
-        .map(|line| line.into_bytes().into_iter().map(|b| b - b'0').collect())
-        .collect()
+impl<T: Copy> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> T {
+        self.cells[y * self.width + x]
+    }
 }
 
-pub fn part1() -> usize {
-    fn look(
-        trees: &Vec<Vec<u8>>,
-        visible: &mut HashSet<Coord>,
-        mut max: u8,
-        coords: impl Iterator<Item = (usize, usize)>,
-    ) {
-        for (x, y) in coords {
-            max = if trees[y][x] > max {
-                visible.insert(Coord { x, y });
-                trees[y][x]
-            } else {
-                max
-            };
-            if max == 9 {
-                break;
-            }
+// space separated values, a line per row
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            let row: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+pub type Forest = Grid<u32>;
+
+// a digit per tree, or space separated heights for trees taller than 9
+impl FromStr for Forest {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<Vec<u32>> = s
+            .lines()
+            .map(|l| {
+                if l.contains(' ') {
+                    l.split_whitespace()
+                        .map(|h| h.parse().map_err(|_| ()))
+                        .collect()
+                } else {
+                    l.chars().map(|c| c.to_digit(10).ok_or(())).collect()
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        let width = rows.first().map_or(0, |r| r.len());
+        if rows.iter().any(|r| r.len() != width) {
+            return Err(());
         }
+        Ok(Grid {
+            width,
+            height: rows.len(),
+            cells: rows.concat(),
+        })
     }
+}
 
-    let trees = input();
-    let mut visible: HashSet<Coord> = HashSet::new();
-    let (h, w) = (trees.len(), trees[0].len());
-    // horizontal
-    for y in 1..h - 1 {
-        // look right
-        let max = trees[y][0];
-        look(&trees, &mut visible, max, (1..(w - 1)).zip(repeat(y)));
-        // look left
-        let max = trees[y][w - 1];
-        look(&trees, &mut visible, max, (1..(w - 1)).rev().zip(repeat(y)));
-    }
-    for x in 1..w - 1 {
-        // look down
-        let max = trees[0][x];
-        look(&trees, &mut visible, max, repeat(x).zip(1..(h - 1)));
-        // look up
-        let max = trees[h - 1][x];
-        look(&trees, &mut visible, max, repeat(x).zip((1..(h - 1)).rev()));
-    }
-    visible.len() + 2 * (w + h - 2) // the border
+// the directions trees are seen along, and how many trees deep one can look; a viewer
+// outside the forest sees a tree when it is no more than `range` trees in from the edge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sight {
+    pub dirs: Vec<(i32, i32)>,
+    pub range: Option<usize>,
 }
 
-fn tree_score(trees: &Vec<Vec<u8>>, x: usize, y: usize) -> usize {
-    fn look(trees: &Vec<Vec<u8>>, max: u8, coords: impl Iterator<Item = (usize, usize)>) -> usize {
-        let mut cnt = 0;
-        for (x, y) in coords {
-            cnt += 1;
-            if trees[y][x] >= max {
-                break;
-            }
+impl Default for Sight {
+    fn default() -> Self {
+        Sight::axes()
+    }
+}
+
+impl Sight {
+    pub fn rays(dirs: Vec<(i32, i32)>, range: Option<usize>) -> Self {
+        assert!(!dirs.contains(&(0, 0)), "a ray needs a direction");
+        Sight { dirs, range }
+    }
+
+    pub fn axes() -> Self {
+        Sight::rays(vec![(-1, 0), (1, 0), (0, -1), (0, 1)], None)
+    }
+
+    pub fn eight() -> Self {
+        let mut sight = Sight::axes();
+        sight.dirs.extend([(-1, -1), (1, -1), (-1, 1), (1, 1)]);
+        sight
+    }
+
+    pub fn with_range(self, range: usize) -> Self {
+        Sight {
+            range: Some(range),
+            ..self
         }
-        cnt
-    }
-
-    let (h, w) = (trees.len(), trees[0].len());
-    let max = trees[y][x];
-    let mut score = 1;
-    // look left
-    score *= look(trees, max, (0..x).rev().zip(repeat(y)));
-    // look right
-    score *= look(trees, max, ((x + 1)..w).zip(repeat(y)));
-    // look up
-    score *= look(trees, max, repeat(x).zip((0..y).rev()));
-    // look down
-    score *= look(trees, max, repeat(x).zip((y + 1)..h));
-    score
+    }
 }
 
-pub fn part2() -> usize {
-    let trees = input();
-    let (h, w) = (trees.len(), trees[0].len());
-    let mut score = 0;
-    for y in 0..h {
-        for x in 0..w {
-            let s = tree_score(&trees, x, y);
-            score = max(s, score);
+// visible from outside, and the scenic score, of every tree
+pub struct Views {
+    pub visible: Grid<bool>,
+    pub scores: Grid<usize>,
+}
+
+impl Forest {
+    pub fn load(filename: &str) -> Self {
+        load::<String>(filename).join("\n").parse().unwrap()
+    }
+
+    fn step(&self, (x, y): (usize, usize), (dx, dy): (i32, i32)) -> Option<(usize, usize)> {
+        let (x, y) = (x as i64 + dx as i64, y as i64 + dy as i64);
+        let inside = (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y);
+        inside.then_some((x as usize, y as usize))
+    }
+
+    // one pass along a line of trees, starting at the edge they look towards and keeping
+    // the trees not yet blocked from view on a stack of falling heights: popping everything
+    // lower leaves the blocking tree on top
+    fn sweep(&self, line: impl Iterator<Item = usize>, range: usize, views: &mut Views) {
+        let mut stack: Vec<(usize, u32)> = vec![];
+        for (i, cell) in line.enumerate() {
+            let h = self.cells[cell];
+            while stack.last().is_some_and(|&(_, top)| top < h) {
+                stack.pop();
+            }
+            let seen = match stack.last() {
+                None => {
+                    views.visible.cells[cell] |= i < range;
+                    i // all the way to the edge
+                }
+                Some(&(j, _)) => i - j,
+            };
+            views.scores.cells[cell] *= seen.min(range);
+            stack.push((i, h));
+        }
+    }
+
+    pub fn views_with(&self, sight: &Sight) -> Views {
+        let (w, h) = (self.width, self.height);
+        let mut views = Views {
+            visible: Grid::new(w, h, false),
+            scores: Grid::new(w, h, 1),
+        };
+        let range = sight.range.unwrap_or(usize::MAX);
+        for &(dx, dy) in &sight.dirs {
+            let back = (-dx, -dy);
+            // every line starts at a tree with nothing further along the ray
+            for y in 0..h {
+                for x in 0..w {
+                    if self.step((x, y), (dx, dy)).is_some() {
+                        continue;
+                    }
+                    let line = std::iter::successors(Some((x, y)), |&c| self.step(c, back));
+                    self.sweep(line.map(|(x, y)| y * w + x), range, &mut views);
+                }
+            }
         }
+        views
+    }
+
+    pub fn views(&self) -> Views {
+        self.views_with(&Sight::axes())
     }
-    score
 }
 
+// walking out from a single tree
+pub fn tree_score(forest: &Forest, x: usize, y: usize, sight: &Sight) -> usize {
+    let h = forest.get(x, y);
+    let range = sight.range.unwrap_or(usize::MAX);
+    sight
+        .dirs
+        .iter()
+        .map(|&d| {
+            let mut cnt = 0;
+            let mut at = (x, y);
+            while let Some(next) = forest.step(at, d).filter(|_| cnt < range) {
+                cnt += 1;
+                if forest.get(next.0, next.1) >= h {
+                    break;
+                }
+                at = next;
+            }
+            cnt
+        })
+        .product()
+}
+
+pub fn count_visible(filename: &str, sight: &Sight) -> usize {
+    let views = Forest::load(filename).views_with(sight);
+    views.visible.cells.iter().filter(|&&v| v).count()
+}
+
+pub fn best_score(filename: &str, sight: &Sight) -> usize {
+    let views = Forest::load(filename).views_with(sight);
+    views.scores.cells.into_iter().max().unwrap()
+}
+
+pub fn part1(filename: &str) -> usize {
+    count_visible(filename, &Sight::axes())
+}
+
+pub fn part2(filename: &str) -> usize {
+    best_score(filename, &Sight::axes())
+}
+
+#[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn test_views() {
+        let forest: Forest = "30373\n25512\n65332\n33549\n35390".parse().unwrap();
+        let views = forest.views();
+        assert_eq!(views.visible.cells.iter().filter(|&&v| v).count(), 21);
+        assert_eq!(views.scores.get(2, 3), 8);
+        assert_eq!(views.scores.to_string().lines().nth(1), Some("0 1 4 1 0"));
+
+        // wider than high, with tall trees
+        let forest: Forest = "3 12 7 12 40 1\n15 2 11 6 5 9\n0 30 12 3 8 2"
+            .parse()
+            .unwrap();
+        assert_eq!((forest.width, forest.height), (6, 3));
+        let views = forest.views();
+        for y in 0..forest.height {
+            for x in 0..forest.width {
+                assert_eq!(
+                    views.scores.get(x, y),
+                    tree_score(&forest, x, y, &Sight::axes())
+                );
+            }
+        }
+        assert!(!views.visible.get(4, 1));
+        assert!(views.visible.get(2, 1));
+    }
+
+    #[test]
+    fn test_sight() {
+        let forest: Forest = "30373\n25512\n65332\n33549\n35390".parse().unwrap();
+        let knight = Sight::rays(vec![(2, 1), (-1, 2), (-2, -1), (1, -2)], None);
+        for sight in [
+            Sight::eight(),
+            Sight::axes().with_range(2),
+            knight.with_range(3),
+        ] {
+            let views = forest.views_with(&sight);
+            for y in 0..forest.height {
+                for x in 0..forest.width {
+                    assert_eq!(views.scores.get(x, y), tree_score(&forest, x, y, &sight));
+                }
+            }
+        }
+        let visible = |sight| {
+            forest
+                .views_with(&sight)
+                .visible
+                .cells
+                .iter()
+                .filter(|&&v| v)
+                .count()
+        };
+        // the 4 in the fourth row shows along the diagonal from the bottom left
+        assert_eq!(visible(Sight::eight()), 22);
+        // only the outer ring
+        assert_eq!(visible(Sight::axes().with_range(1)), 16);
+    }
+
     #[test]
     fn test_part1() {
-        let num = super::part1();
+        let num = super::part1("data/day8.txt");
         println!("Number of visible trees: {}", num);
         assert_eq!(num, 1703);
     }
 
     #[test]
     fn test_part2() {
-        let score = super::part2();
+        let score = super::part2("data/day8.txt");
         println!("Highest scenic score: {}", score);
         assert_eq!(score, 496650);
     }
@@ -1,12 +1,20 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+};
 
 use crate::util::load;
 
-enum Dir {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dir {
     Up,
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
 impl FromStr for Dir {
@@ -18,119 +26,531 @@ impl FromStr for Dir {
             "D" => Ok(Dir::Down),
             "L" => Ok(Dir::Left),
             "R" => Ok(Dir::Right),
+            "UL" => Ok(Dir::UpLeft),
+            "UR" => Ok(Dir::UpRight),
+            "DL" => Ok(Dir::DownLeft),
+            "DR" => Ok(Dir::DownRight),
             _ => Err(()),
         }
     }
 }
 
-struct Move {
-    dir: Dir,
-    dist: i32,
+impl Dir {
+    pub fn delta(&self) -> (i32, i32) {
+        match self {
+            Dir::Up => (0, 1),
+            Dir::Down => (0, -1),
+            Dir::Left => (-1, 0),
+            Dir::Right => (1, 0),
+            Dir::UpLeft => (-1, 1),
+            Dir::UpRight => (1, 1),
+            Dir::DownLeft => (-1, -1),
+            Dir::DownRight => (1, -1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub dir: Dir,
+    pub dist: i32,
 }
 
 impl FromStr for Move {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens: Vec<&str> = s.split(' ').collect();
+        let (dir, dist) = s.split_once(' ').ok_or(())?;
         Ok(Move {
-            dir: tokens[0].parse().unwrap(),
-            dist: tokens[1].parse().unwrap(),
+            dir: dir.parse()?,
+            dist: dist.parse().map_err(|_| ())?,
         })
     }
 }
 
-fn delta_with_abs_max_1(d: i32) -> i32 {
-    let signum = if d != 0 { d / d.abs() } else { 0 };
-    signum * d.abs().min(1)
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug, Default)]
+pub struct Coord {
+    pub x: i32,
+    pub y: i32,
+}
+
+// the four directions a straight run of positions can go in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Row,
+    Col,
+    Diag,
+    Anti,
+}
+
+const AXES: [Axis; 4] = [Axis::Row, Axis::Col, Axis::Diag, Axis::Anti];
+
+impl Axis {
+    // which line of this direction a position is on
+    fn key(self, c: Coord) -> i32 {
+        match self {
+            Axis::Row => c.y,
+            Axis::Col => c.x,
+            Axis::Diag => c.x - c.y,
+            Axis::Anti => c.x + c.y,
+        }
+    }
+
+    // and where along it
+    fn pos(self, c: Coord) -> i32 {
+        match self {
+            Axis::Col => c.y,
+            _ => c.x,
+        }
+    }
+
+    fn at(self, key: i32, pos: i32) -> Coord {
+        match self {
+            Axis::Row => Coord { x: pos, y: key },
+            Axis::Col => Coord { x: key, y: pos },
+            Axis::Diag => Coord {
+                x: pos,
+                y: pos - key,
+            },
+            Axis::Anti => Coord {
+                x: pos,
+                y: key - pos,
+            },
+        }
+    }
+}
+
+fn run_contains(runs: &[(i32, i32)], pos: i32) -> bool {
+    let i = runs.partition_point(|&(_, to)| to < pos);
+    runs.get(i).is_some_and(|&(from, _)| from <= pos)
 }
-#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
-struct Coord {
-    x: i32,
-    y: i32,
+
+// visited positions as runs along rows, columns and both diagonals; a position can
+// be on runs of several directions, `len` counts it once
+#[derive(Debug, Clone, Default)]
+pub struct Trail {
+    lines: [BTreeMap<i32, Vec<(i32, i32)>>; 4],
+    len: usize,
 }
 
-impl Coord {
-    fn move_1(&mut self, dx: i32, dy: i32) {
-        self.x += dx;
-        self.y += dy;
+impl Trail {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, c: &Coord) -> bool {
+        AXES.iter().any(|&axis| {
+            self.lines[axis as usize]
+                .get(&axis.key(*c))
+                .is_some_and(|runs| run_contains(runs, axis.pos(*c)))
+        })
+    }
+
+    // every position, only meant for small trails
+    pub fn points(&self) -> HashSet<Coord> {
+        let mut points = HashSet::new();
+        for axis in AXES {
+            for (&key, runs) in &self.lines[axis as usize] {
+                for &(from, to) in runs {
+                    points.extend((from..=to).map(|pos| axis.at(key, pos)));
+                }
+            }
+        }
+        points
+    }
+
+    // merges with the runs along the same line it overlaps or touches
+    fn insert_run(&mut self, axis: Axis, key: i32, mut from: i32, mut to: i32) {
+        let runs = self.lines[axis as usize].entry(key).or_default();
+        let first = runs.partition_point(|&(_, t)| t < from - 1);
+        let last = runs.partition_point(|&(f, _)| f <= to + 1);
+        // the parts of from..=to not on this line yet
+        let (a, b) = (from, to);
+        let mut gaps = vec![];
+        let mut next = a;
+        for &(f, t) in &runs[first..last] {
+            if next < f.min(b + 1) {
+                gaps.push((next, f.min(b + 1) - 1));
+            }
+            next = next.max(t + 1);
+            from = from.min(f);
+            to = to.max(t);
+        }
+        if next <= b {
+            gaps.push((next, b));
+        }
+        runs.splice(first..last, [(from, to)]);
+        for (from, to) in gaps {
+            self.len += (to - from + 1) as usize - self.crossings(axis, key, from, to);
+        }
+    }
+
+    // positions of a run that lie on runs in the other directions, each of which
+    // crosses it at most once
+    fn crossings(&self, axis: Axis, key: i32, from: i32, to: i32) -> usize {
+        let mut others = AXES.into_iter().filter(|&other| other != axis);
+        if from == to {
+            let c = axis.at(key, from);
+            return others.any(|other| {
+                self.lines[other as usize]
+                    .get(&other.key(c))
+                    .is_some_and(|runs| run_contains(runs, other.pos(c)))
+            }) as usize;
+        }
+        let mut seen = HashSet::new();
+        for other in others {
+            let start = other.key(axis.at(key, from));
+            let end = other.key(axis.at(key, to));
+            // how fast the other direction's key changes along this line, never 0
+            let slope = other.key(axis.at(key, from + 1)) - start;
+            for (&k, runs) in self.lines[other as usize].range(start.min(end)..=start.max(end)) {
+                if (k - start) % slope != 0 {
+                    continue;
+                }
+                let pos = from + (k - start) / slope;
+                if run_contains(runs, other.pos(axis.at(key, pos))) {
+                    seen.insert(pos);
+                }
+            }
+        }
+        seen.len()
+    }
+
+    pub fn insert(&mut self, c: Coord) {
+        self.insert_run(Axis::Row, c.y, c.x, c.x);
+    }
+
+    // the `n` positions after `from` going `(dx, dy)`, one step in any direction
+    pub fn insert_line(&mut self, from: Coord, (dx, dy): (i32, i32), n: i32) {
+        if n == 0 {
+            return;
+        }
+        let axis = match (dx, dy) {
+            (_, 0) => Axis::Row,
+            (0, _) => Axis::Col,
+            _ if dx == dy => Axis::Diag,
+            _ => Axis::Anti,
+        };
+        let to = Coord {
+            x: from.x + dx * n,
+            y: from.y + dy * n,
+        };
+        let start = Coord {
+            x: from.x + dx,
+            y: from.y + dy,
+        };
+        let (a, b) = (axis.pos(start), axis.pos(to));
+        self.insert_run(axis, axis.key(to), a.min(b), a.max(b));
     }
+}
+
+// where a knot goes when the knot in front of it has moved; only where the two are
+// relative to each other may matter, which is what lets a straight rope be fast-forwarded
+pub trait Follow {
+    fn follow(&self, knot: Coord, leader: Coord) -> Coord;
+}
 
-    /**
-     * Either for (x,y) or (y,x)
-     *   Delta    Move
-     *   (0,0) -> (0,0)
-     *   (1,0) -> (0,0)
-     *   (1,1) -> (0,0)
-     *   (2,0) -> (1,0)
-     *   (2,1) -> (1,1)
-     */
-    fn move_1_closer_to(&mut self, other: &Self) {
-        let dx = other.x - self.x;
-        let dy = other.y - self.y;
-        if dx.abs() > 1 || dy.abs() > 1 {
-            self.x += delta_with_abs_max_1(dx);
-            self.y += delta_with_abs_max_1(dy);
+/**
+ * The knot stays while it is within `max` steps (diagonals included) of its leader,
+ * otherwise it takes one step towards it. With max 1, either for (x,y) or (y,x)
+ *   Delta    Move
+ *   (0,0) -> (0,0)
+ *   (1,0) -> (0,0)
+ *   (1,1) -> (0,0)
+ *   (2,0) -> (1,0)
+ *   (2,1) -> (1,1)
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Stretch(pub i32);
+
+impl Follow for Stretch {
+    fn follow(&self, knot: Coord, leader: Coord) -> Coord {
+        let (dx, dy) = (leader.x - knot.x, leader.y - knot.y);
+        if dx.abs() > self.0 || dy.abs() > self.0 {
+            Coord {
+                x: knot.x + dx.signum(),
+                y: knot.y + dy.signum(),
+            }
+        } else {
+            knot
         }
     }
 }
 
-struct Rope {
-    num_knots: usize,
-    knots: Vec<Coord>,
-    trail: HashSet<Coord>,
+// a single step of the head and the knots dragged along, knots that stay are left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub step: usize,
+    pub moved: Vec<(usize, Coord, Coord)>,
+}
+
+pub struct Rope<F: Follow = Stretch> {
+    pub knots: Vec<Coord>,
+    pub trails: Vec<Trail>,
+    pub travelled: Vec<usize>, // steps taken by every knot
+    pub steps: usize,
+    follow: F,
 }
 
 impl Rope {
     pub fn new(num_knots: usize) -> Self {
-        Self {
-            num_knots,
-            knots: (0..num_knots).map(|_| Coord { x: 0, y: 0 }).collect(),
-            trail: HashSet::from([Coord { x: 0, y: 0 }]),
+        Rope::with_rule(num_knots, Stretch(1))
+    }
+}
+
+impl<F: Follow> Rope<F> {
+    pub fn with_rule(num_knots: usize, follow: F) -> Self {
+        let mut rope = Self {
+            knots: vec![Coord::default(); num_knots],
+            trails: vec![Trail::default(); num_knots],
+            travelled: vec![0; num_knots],
+            steps: 0,
+            follow,
+        };
+        for trail in rope.trails.iter_mut() {
+            trail.insert(Coord::default());
         }
+        rope
     }
 
-    fn trail_length(&self) -> usize {
-        self.trail.len()
+    pub fn tail(&self) -> &Trail {
+        self.trails.last().unwrap()
     }
 
-    fn move_head(&mut self, mv: &Move) {
-        let (dx, dy) = match mv.dir {
-            Dir::Up => (0, 1),
-            Dir::Down => (0, -1),
-            Dir::Left => (-1, 0),
-            Dir::Right => (1, 0),
-        };
-        for _step in 0..mv.dist {
-            self.knots[0].move_1(dx, dy);
-            for i in 1..self.num_knots {
-                let dst = self.knots[i - 1];
-                self.knots[i].move_1_closer_to(&dst);
+    pub fn step(&mut self, (dx, dy): (i32, i32)) -> Event {
+        self.steps += 1;
+        let mut moved = vec![];
+        for i in 0..self.knots.len() {
+            let from = self.knots[i];
+            let to = match i {
+                0 => Coord {
+                    x: from.x + dx,
+                    y: from.y + dy,
+                },
+                _ => self.follow.follow(from, self.knots[i - 1]),
+            };
+            // a knot that stays may still have to catch up under another rule,
+            // so every knot is looked at
+            if to != from {
+                self.knots[i] = to;
+                self.trails[i].insert(to);
+                self.travelled[i] += 1;
+                moved.push((i, from, to));
+            }
+        }
+        Event {
+            step: self.steps,
+            moved,
+        }
+    }
+
+    // steps through a move as it is iterated
+    pub fn events<'a>(&'a mut self, mv: &Move) -> impl Iterator<Item = Event> + 'a {
+        let delta = mv.dir.delta();
+        (0..mv.dist).map(move |_| self.step(delta))
+    }
+
+    // once a step moves every knot the same way as the head, all the following steps
+    // of the move do so too, and the rest is skipped in one go
+    pub fn move_head(&mut self, mv: &Move) {
+        let (dx, dy) = mv.dir.delta();
+        for done in 1..=mv.dist {
+            let event = self.step((dx, dy));
+            let straight = event.moved.len() == self.knots.len()
+                && event
+                    .moved
+                    .iter()
+                    .all(|&(_, from, to)| (to.x - from.x, to.y - from.y) == (dx, dy));
+            if straight {
+                let left = mv.dist - done;
+                for i in 0..self.knots.len() {
+                    let from = self.knots[i];
+                    self.trails[i].insert_line(from, (dx, dy), left);
+                    self.knots[i] = Coord {
+                        x: from.x + dx * left,
+                        y: from.y + dy * left,
+                    };
+                    self.travelled[i] += left as usize;
+                }
+                self.steps += left as usize;
+                return;
             }
-            self.trail.insert(*self.knots.last().unwrap());
         }
     }
 }
 
-fn move_rope(moves: Vec<Move>, num_knots: usize) -> usize {
+pub fn move_rope(moves: &[Move], num_knots: usize) -> usize {
     let mut rope = Rope::new(num_knots);
     for m in moves {
-        rope.move_head(&m);
+        rope.move_head(m);
     }
-    rope.trail_length()
+    rope.tail().len()
 }
 
 pub fn part1(filename: &str) -> usize {
     let moves: Vec<Move> = load(filename);
-    move_rope(moves, 2)
+    move_rope(&moves, 2)
 }
 
 pub fn part2(filename: &str) -> usize {
     let moves: Vec<Move> = load(filename);
-    move_rope(moves, 10)
+    move_rope(&moves, 10)
 }
 
+#[cfg(test)]
 mod tests {
+    use std::ops::Range;
+
+    use super::*;
+    use crate::check::{differential, remove_one};
+
+    #[test]
+    fn test_rope() {
+        let moves: Vec<Move> = ["R 4", "U 4", "L 3", "D 1", "R 4", "D 1", "L 5", "R 2"]
+            .iter()
+            .map(|m| m.parse().unwrap())
+            .collect();
+        assert_eq!(move_rope(&moves, 2), 13);
+        assert_eq!(move_rope(&moves, 10), 1);
+
+        let mut rope = Rope::new(3);
+        let events: Vec<Event> = rope.events(&"UR 3".parse().unwrap()).collect();
+        let c = |x, y| Coord { x, y };
+        assert_eq!(events[0].moved, [(0, c(0, 0), c(1, 1))]);
+        assert_eq!(
+            events[1].moved,
+            [(0, c(1, 1), c(2, 2)), (1, c(0, 0), c(1, 1))]
+        );
+        assert_eq!(events[2].step, 3);
+        assert_eq!(rope.travelled, [3, 2, 1]);
+        assert_eq!(rope.trails[1].len(), 3);
+
+        // a looser rope drags behind further
+        let mut rope = Rope::with_rule(2, Stretch(3));
+        rope.move_head(&"L 10".parse().unwrap());
+        assert_eq!((rope.knots[1], rope.tail().len()), (c(-7, 0), 8));
+        assert_eq!("X 1".parse::<Move>(), Err(()));
+
+        // a knot can move while the one in front of it stays
+        let mut rope = Rope::with_rule(3, SideStep);
+        let event = rope.step((1, 0));
+        assert_eq!(event.moved, [(0, c(0, 0), c(1, 0)), (2, c(0, 0), c(-1, 0))]);
+    }
+
+    // steps off a knot it is on top of
+    struct SideStep;
+
+    impl Follow for SideStep {
+        fn follow(&self, knot: Coord, leader: Coord) -> Coord {
+            if knot == leader {
+                Coord {
+                    x: knot.x - 1,
+                    ..knot
+                }
+            } else {
+                Stretch(1).follow(knot, leader)
+            }
+        }
+    }
+
+    fn fast_forward_differential(seeds: Range<u64>) {
+        differential(
+            "day9",
+            seeds,
+            |rng| {
+                let dirs = ["U", "D", "L", "R", "UL", "UR", "DL", "DR"];
+                let moves: Vec<Move> = (0..rng.range(1, 20))
+                    .map(|_| format!("{} {}", rng.pick(&dirs), rng.range(1, 40)))
+                    .map(|m| m.parse().unwrap())
+                    .collect();
+                Some((moves, rng.range(1, 12) as usize, rng.range(1, 3) as i32))
+            },
+            |(moves, knots, stretch)| {
+                remove_one(moves)
+                    .into_iter()
+                    .map(|moves| (moves, *knots, *stretch))
+                    .collect()
+            },
+            |(moves, knots, stretch)| {
+                let mut rope = Rope::with_rule(*knots, Stretch(*stretch));
+                moves.iter().for_each(|m| rope.move_head(m));
+                (
+                    rope.knots,
+                    rope.trails
+                        .iter()
+                        .map(|t| (t.len(), t.points()))
+                        .collect::<Vec<_>>(),
+                    rope.travelled,
+                    rope.steps,
+                )
+            },
+            // one step at a time
+            |(moves, knots, stretch)| {
+                let mut rope = Rope::with_rule(*knots, Stretch(*stretch));
+                moves.iter().for_each(|m| rope.events(m).for_each(drop));
+                (
+                    rope.knots,
+                    rope.trails
+                        .iter()
+                        .map(|t| (t.len(), t.points()))
+                        .collect::<Vec<_>>(),
+                    rope.travelled,
+                    rope.steps,
+                )
+            },
+        );
+    }
+
+    #[test]
+    fn test_fast_forward_differential() {
+        fast_forward_differential(0..100);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_fast_forward_sweep() {
+        fast_forward_differential(0..300);
+    }
+
+    #[test]
+    fn test_long_moves() {
+        let moves: Vec<Move> = ["R 50000000", "U 100000", "L 50000000"]
+            .iter()
+            .map(|m| m.parse().unwrap())
+            .collect();
+        let mut rope = Rope::new(10);
+        moves.iter().for_each(|m| rope.move_head(m));
+        assert_eq!(rope.knots[9], Coord { x: 9, y: 100000 });
+        // along the bottom, up the side and back along the top, cutting the corners
+        assert_eq!(rope.tail().len(), 2 * 50000000 + 100000 - 26);
+        assert!(rope.tail().contains(&Coord {
+            x: 50000000,
+            y: 50000
+        }));
+    }
+
+    #[test]
+    fn test_long_lines() {
+        let moves: Vec<Move> = ["U 4000000", "DR 2000000", "L 3000000", "UR 2000000"]
+            .iter()
+            .map(|m| m.parse().unwrap())
+            .collect();
+        let mut rope = Rope::new(2);
+        moves.iter().for_each(|m| rope.move_head(m));
+        let c = |x, y| Coord { x, y };
+        assert_eq!(rope.knots, [c(1000000, 4000000), c(1000000, 3999999)]);
+        // the row crosses the column once, the last diagonal crosses the column and
+        // the first diagonal
+        assert_eq!(rope.tail().len(), 11000000 - 7);
+        assert!(rope.tail().contains(&c(0, 2999999)));
+        assert!(rope.tail().contains(&c(500000, 3499999)));
+        assert!(!rope.tail().contains(&c(0, 4000000)));
+    }
+
     #[test]
     fn test_part1() {
         let num = super::part1("data/day9.txt");
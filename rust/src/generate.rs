@@ -0,0 +1,917 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Write,
+    io,
+};
+
+use crate::{day10, util::TempInput};
+
+// SplitMix64, so generated inputs are identical on every platform and release
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    // uniform in `lo..=hi`
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        assert!(lo <= hi, "empty range");
+        lo + ((self.next_u64() as u128 * (hi - lo + 1) as u128) >> 64) as i64
+    }
+
+    // true with a probability of `num / den`
+    pub fn chance(&mut self, num: usize, den: usize) -> bool {
+        self.below(den) < num
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    fn letters(&mut self, alphabet: &[u8], len: usize) -> String {
+        (0..len).map(|_| *self.pick(alphabet) as char).collect()
+    }
+}
+
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+// sizes in the order of magnitude of the real puzzle inputs
+pub fn default_size(day: u32) -> usize {
+    match day {
+        1 => 250,
+        2 | 4 | 9 => 1000,
+        3 => 300,
+        5 => 50,
+        6 => 4096,
+        7 => 150,
+        8 | 23 => 99,
+        10 => 240,
+        11 => 8,
+        12 => 100,
+        13 => 150,
+        14 => 150,
+        15 => 30,
+        16 => 50,
+        17 => 10000,
+        18 => 2000,
+        19 => 30,
+        20 => 5000,
+        21 => 2000,
+        22 => 4000,
+        24 => 120,
+        25 => 120,
+        _ => panic!("no generator for day {}", day),
+    }
+}
+
+pub fn generate(day: u32, seed: u64, size: usize) -> String {
+    let gen = match day {
+        1 => day1,
+        2 => day2,
+        3 => day3,
+        4 => day4,
+        5 => day5,
+        6 => day6,
+        7 => day7,
+        8 => day8,
+        9 => day9,
+        10 => day10,
+        11 => day11,
+        12 => day12,
+        13 => day13,
+        14 => day14,
+        15 => day15,
+        16 => day16,
+        17 => day17,
+        18 => day18,
+        19 => day19,
+        20 => day20,
+        21 => day21,
+        22 => day22,
+        23 => day23,
+        24 => day24,
+        25 => day25,
+        _ => panic!("no generator for day {}", day),
+    };
+    // mix in the day so every day gets a different stream for the same seed
+    let mut rng = Rng::new(seed ^ (day as u64).wrapping_mul(0x2545f4914f6cdd1d));
+    gen(&mut rng, size)
+}
+
+pub fn write_input(day: u32, seed: u64, size: usize) -> io::Result<TempInput> {
+    TempInput::new(generate(day, seed, size))
+}
+
+fn day1(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for elf in 0..size.max(3) {
+        if elf > 0 {
+            s.push('\n');
+        }
+        for _ in 0..rng.range(1, 12) {
+            writeln!(s, "{}", rng.range(1000, 15000)).unwrap();
+        }
+    }
+    s
+}
+
+fn day2(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size {
+        writeln!(
+            s,
+            "{} {}",
+            rng.pick(&['A', 'B', 'C']),
+            rng.pick(&['X', 'Y', 'Z'])
+        )
+        .unwrap();
+    }
+    s
+}
+
+// every rucksack has exactly one item in both compartments, every group of three
+// exactly one badge in common
+fn day3(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    let mut items: Vec<u8> = [LOWER, UPPER].concat();
+    for _ in 0..(size / 3).max(1) {
+        rng.shuffle(&mut items);
+        let badge = items[0];
+        for r in 0..3 {
+            let shared = items[1 + r];
+            let private = &items[4 + 16 * r..4 + 16 * (r + 1)];
+            let len = rng.below(11) + 2;
+            let mut left = vec![badge, shared];
+            let mut right = vec![shared];
+            left.extend((2..len).map(|_| *rng.pick(&private[..8])));
+            right.extend((1..len).map(|_| *rng.pick(&private[8..])));
+            rng.shuffle(&mut left);
+            rng.shuffle(&mut right);
+            left.append(&mut right);
+            writeln!(s, "{}", String::from_utf8(left).unwrap()).unwrap();
+        }
+    }
+    s
+}
+
+fn day4(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size {
+        let (a1, b1) = (rng.range(1, 99), rng.range(1, 99));
+        let (a2, b2) = (rng.range(1, 99), rng.range(1, 99));
+        let (a1, b1, a2, b2) = (a1.min(b1), a1.max(b1), a2.min(b2), a2.max(b2));
+        writeln!(s, "{}-{},{}-{}", a1, b1, a2, b2).unwrap();
+    }
+    s
+}
+
+// 9 stacks, moves never empty a stack completely so the top crates are always defined
+fn day5(rng: &mut Rng, size: usize) -> String {
+    let mut stacks: Vec<Vec<u8>> = (0..9)
+        .map(|_| (0..rng.range(1, 8)).map(|_| *rng.pick(UPPER)).collect())
+        .collect();
+    let height = stacks.iter().map(|st| st.len()).max().unwrap();
+    let mut s = String::new();
+    for row in (0..height).rev() {
+        let line: Vec<String> = stacks
+            .iter()
+            .map(|st| match st.get(row) {
+                Some(c) => format!("[{}]", *c as char),
+                None => "   ".to_owned(),
+            })
+            .collect();
+        writeln!(s, "{}", line.join(" ")).unwrap();
+    }
+    let footer: Vec<String> = (1..=9).map(|i| format!(" {} ", i)).collect();
+    writeln!(s, "{}\n", footer.join(" ")).unwrap();
+    for _ in 0..size {
+        let from: Vec<usize> = (0..9).filter(|&i| stacks[i].len() > 1).collect();
+        if from.is_empty() {
+            break;
+        }
+        let from = *rng.pick(&from);
+        let to = (from + 1 + rng.below(8)) % 9;
+        let num = 1 + rng.below((stacks[from].len() - 1).min(8));
+        let l = stacks[from].len();
+        let mut moved = stacks[from].split_off(l - num);
+        stacks[to].append(&mut moved);
+        writeln!(s, "move {} from {} to {}", num, from + 1, to + 1).unwrap();
+    }
+    s
+}
+
+// a prefix of only 3 letters cannot contain a marker, followed by 14 distinct letters
+fn day6(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(14);
+    let mut letters = LOWER.to_vec();
+    rng.shuffle(&mut letters);
+    let prefix = rng.below(size - 14 + 1);
+    let mut s = rng.letters(&letters[..3], prefix);
+    rng.shuffle(&mut letters);
+    s.push_str(std::str::from_utf8(&letters[..14]).unwrap());
+    s.push_str(&rng.letters(LOWER, size - prefix - 14));
+    s.push('\n');
+    s
+}
+
+// total disk usage between 40M and 70M, so part 2 always has something to delete
+fn day7(rng: &mut Rng, size: usize) -> String {
+    struct Dir {
+        name: String,
+        dirs: Vec<usize>,
+        files: Vec<(String, u64)>,
+    }
+
+    fn unique_name(rng: &mut Rng, taken: &mut BTreeSet<String>, ext: bool) -> String {
+        loop {
+            let len = rng.range(1, 8) as usize;
+            let mut name = rng.letters(LOWER, len);
+            if ext {
+                name.push('.');
+                name.push_str(&rng.letters(LOWER, 3));
+            }
+            if taken.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    fn session(s: &mut String, tree: &[Dir], dir: usize, rng: &mut Rng) {
+        s.push_str("$ ls\n");
+        let mut listing: Vec<String> = tree[dir]
+            .dirs
+            .iter()
+            .map(|&d| format!("dir {}", tree[d].name))
+            .chain(
+                tree[dir]
+                    .files
+                    .iter()
+                    .map(|(name, sz)| format!("{} {}", sz, name)),
+            )
+            .collect();
+        rng.shuffle(&mut listing);
+        for l in listing {
+            writeln!(s, "{}", l).unwrap();
+        }
+        for &d in &tree[dir].dirs {
+            writeln!(s, "$ cd {}", tree[d].name).unwrap();
+            session(s, tree, d, rng);
+            s.push_str("$ cd ..\n");
+        }
+    }
+
+    let mut tree = vec![Dir {
+        name: "/".to_owned(),
+        dirs: vec![],
+        files: vec![],
+    }];
+    let mut taken: Vec<BTreeSet<String>> = vec![BTreeSet::new()];
+    for d in 1..size.max(1) {
+        let parent = rng.below(d);
+        let name = unique_name(rng, &mut taken[parent], false);
+        tree.push(Dir {
+            name,
+            dirs: vec![],
+            files: vec![],
+        });
+        tree[parent].dirs.push(d);
+        taken.push(BTreeSet::new());
+    }
+    let mut weights = Vec::new();
+    for d in 0..tree.len() {
+        for _ in 0..rng.range(if d == 0 { 1 } else { 0 }, 4) {
+            let name = unique_name(rng, &mut taken[d], true);
+            let weight = rng.range(1, 1000) as u64;
+            weights.push(weight);
+            tree[d].files.push((name, weight));
+        }
+    }
+    let total = rng.range(41_000_000, 69_000_000) as u64;
+    let sum: u64 = weights.iter().sum();
+    for dir in tree.iter_mut() {
+        for (_, sz) in dir.files.iter_mut() {
+            *sz = (*sz * total / sum).max(1);
+        }
+    }
+    let mut s = "$ cd /\n".to_owned();
+    session(&mut s, &tree, 0, rng);
+    s
+}
+
+fn day8(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(3);
+    let mut s = String::new();
+    for _ in 0..size {
+        writeln!(s, "{}", rng.letters(b"0123456789", size)).unwrap();
+    }
+    s
+}
+
+fn day9(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size {
+        writeln!(
+            s,
+            "{} {}",
+            rng.pick(&['U', 'D', 'L', 'R']),
+            rng.range(1, 20)
+        )
+        .unwrap();
+    }
+    s
+}
+
+// screenfuls of letters, enough for `size` cycles, each ending on two noops that become the
+// `addx` taking the register back to where the next one starts; the sprite starts over the top
+// left pixels, so redraw until the first letter allows it
+fn day10(rng: &mut Rng, size: usize) -> String {
+    let isa = day10::Isa::default();
+    let frames = size.div_ceil(240).max(1);
+    let mut screens = vec![];
+    while screens.len() < frames {
+        let text = rng.letters(b"ABCEFGHIJKLOPRSUYZ", 8);
+        if let Ok(mut frame) = day10::compile_text(&text) {
+            if !frame.ends_with(&[day10::Instr::Noop, day10::Instr::Noop]) {
+                continue;
+            }
+            if screens.len() + 1 < frames {
+                let x: i32 = 1 + frame
+                    .iter()
+                    .map(|i| match i {
+                        day10::Instr::Addx(v) => *v,
+                        _ => 0,
+                    })
+                    .sum::<i32>();
+                frame.truncate(frame.len() - 2);
+                frame.push(day10::Instr::Addx(1 - x));
+            }
+            screens.push(frame);
+        }
+    }
+    screens
+        .concat()
+        .iter()
+        .map(|i| isa.show(i) + "\n")
+        .collect()
+}
+
+// test divisors are distinct primes up to 19, as the solver assumes
+fn day11(rng: &mut Rng, size: usize) -> String {
+    let n = size.clamp(2, 8);
+    let mut primes = vec![2, 3, 5, 7, 11, 13, 17, 19];
+    rng.shuffle(&mut primes);
+    let mut monkeys = Vec::new();
+    for (m, divisor) in primes.into_iter().take(n).enumerate() {
+        let items: Vec<String> = (0..rng.range(1, 6))
+            .map(|_| rng.range(50, 99).to_string())
+            .collect();
+        let op = match rng.below(8) {
+            0 => "old * old".to_owned(),
+            1..=3 => format!("old * {}", rng.range(2, 19)),
+            _ => format!("old + {}", rng.range(1, 8)),
+        };
+        let t = (m + 1 + rng.below(n - 1)) % n;
+        let f = if n == 2 {
+            t
+        } else {
+            let others: Vec<usize> = (0..n).filter(|&o| o != m && o != t).collect();
+            *rng.pick(&others)
+        };
+        monkeys.push(format!(
+            "Monkey {}:\n  Starting items: {}\n  Operation: new = {}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+            m,
+            items.join(", "),
+            op,
+            divisor,
+            t,
+            f
+        ));
+    }
+    monkeys.join("\n")
+}
+
+// a random staircase path from S to E climbs steadily from a to z
+fn day12(rng: &mut Rng, size: usize) -> String {
+    let (w, h) = (size.max(30), (size / 4).max(5));
+    let mut grid: Vec<Vec<u8>> = (0..h)
+        .map(|_| (0..w).map(|_| *rng.pick(LOWER)).collect())
+        .collect();
+    let end = rng.below(h);
+    let mut y = rng.below(h);
+    let mut path = vec![];
+    for x in 0..w {
+        let target = match x {
+            _ if x == w - 1 => end,
+            _ if rng.chance(1, 2) => y,
+            _ => rng.below(h),
+        };
+        path.push((x, y));
+        while y != target {
+            y = if target > y { y + 1 } else { y - 1 };
+            path.push((x, y));
+        }
+    }
+    let len = path.len() - 1;
+    for (i, &(x, y)) in path.iter().enumerate() {
+        grid[y][x] = b'a' + (25 * i / len) as u8;
+    }
+    let (sx, sy) = path[0];
+    grid[sy][sx] = b'S';
+    grid[end][w - 1] = b'E';
+    let mut s = String::new();
+    for row in grid {
+        writeln!(s, "{}", String::from_utf8(row).unwrap()).unwrap();
+    }
+    s
+}
+
+// top-level packets have at least two items, so none compare equal to a divider packet
+fn day13(rng: &mut Rng, size: usize) -> String {
+    fn packet(rng: &mut Rng, depth: usize) -> String {
+        let len = if depth == 0 {
+            rng.range(2, 5)
+        } else {
+            rng.range(0, 4)
+        };
+        let items: Vec<String> = (0..len)
+            .map(|_| {
+                if depth < 4 && rng.chance(1, 3) {
+                    packet(rng, depth + 1)
+                } else {
+                    rng.range(0, 10).to_string()
+                }
+            })
+            .collect();
+        format!("[{}]", items.join(","))
+    }
+
+    let pairs: Vec<String> = (0..size.max(1))
+        .map(|_| format!("{}\n{}\n", packet(rng, 0), packet(rng, 0)))
+        .collect();
+    pairs.join("\n")
+}
+
+// paths that would let the sand pile up to its source are left out
+fn day14(rng: &mut Rng, size: usize) -> String {
+    fn sand_escapes(rocks: &HashSet<(i64, i64)>) -> bool {
+        let lowest = rocks.iter().map(|(_, y)| *y).max().unwrap_or(0);
+        let mut filled = rocks.clone();
+        loop {
+            let (mut x, mut y) = (500, 0);
+            loop {
+                if y > lowest {
+                    return true;
+                }
+                match [x, x - 1, x + 1]
+                    .into_iter()
+                    .find(|nx| !filled.contains(&(*nx, y + 1)))
+                {
+                    Some(nx) => (x, y) = (nx, y + 1),
+                    None => break,
+                }
+            }
+            if y == 0 {
+                return false;
+            }
+            filled.insert((x, y));
+        }
+    }
+
+    let mut s = String::new();
+    let mut rocks = HashSet::new();
+    for _ in 0..size.max(1) {
+        let (mut x, mut y) = (rng.range(460, 540), rng.range(10, 60));
+        let mut points = vec![(x, y)];
+        for segment in 0..rng.range(1, 5) {
+            let d = rng.range(1, 8) * if rng.chance(1, 2) { 1 } else { -1 };
+            if segment % 2 == 0 {
+                x += d;
+            } else {
+                y = (y + d).max(5);
+            }
+            points.push((x, y));
+        }
+        let mut with_path = rocks.clone();
+        for w in points.windows(2) {
+            let ((x1, y1), (x2, y2)) = (w[0], w[1]);
+            for x in x1.min(x2)..=x1.max(x2) {
+                for y in y1.min(y2)..=y1.max(y2) {
+                    with_path.insert((x, y));
+                }
+            }
+        }
+        if sand_escapes(&with_path) {
+            rocks = with_path;
+            let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+            writeln!(s, "{}", points.join(" -> ")).unwrap();
+        }
+    }
+    s
+}
+
+// eight big sensors around a hidden beacon cover everything but that single spot
+// (axis sensors cover |dy| < |dx| and vice versa, diagonal sensors the diagonals);
+// `size` extra sensors never reach the hidden beacon
+fn day15(rng: &mut Rng, size: usize) -> String {
+    const MAX: i64 = 4000000;
+    let (px, py) = (rng.range(0, MAX - 1), rng.range(0, MAX - 1));
+    let mut sensors = Vec::new();
+    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+        let a = rng.range(2 * MAX, 2 * MAX + MAX / 4);
+        let (sx, sy) = (px + dx * a, py + dy * a);
+        sensors.push(((sx, sy), (sx + dy * (a - 1), sy + dx * (a - 1))));
+    }
+    for (dx, dy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+        let b = rng.range(5 * MAX / 4, 3 * MAX / 2);
+        let (sx, sy) = (px + dx * b, py + dy * b);
+        sensors.push(((sx, sy), (sx + dx * (2 * b - 1), sy)));
+    }
+    while sensors.len() < size + 8 {
+        let (sx, sy) = (rng.range(0, MAX), rng.range(0, MAX));
+        let d = (sx - px).abs() + (sy - py).abs();
+        if d < 2 {
+            continue;
+        }
+        let r = rng.range(1, (d - 1).min(MAX / 4));
+        let k = rng.range(0, r);
+        let (bx, by) = (
+            sx + k * if rng.chance(1, 2) { 1 } else { -1 },
+            sy + (r - k) * if rng.chance(1, 2) { 1 } else { -1 },
+        );
+        sensors.push(((sx, sy), (bx, by)));
+    }
+    rng.shuffle(&mut sensors);
+    let mut s = String::new();
+    for ((sx, sy), (bx, by)) in sensors {
+        writeln!(
+            s,
+            "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+            sx, sy, bx, by
+        )
+        .unwrap();
+    }
+    s
+}
+
+// connected network of at most 60 rooms and 15 working valves
+fn day16(rng: &mut Rng, size: usize) -> String {
+    let n = size.clamp(2, 60);
+    let mut names = BTreeSet::from(["AA".to_owned()]);
+    while names.len() < n {
+        names.insert(rng.letters(UPPER, 2));
+    }
+    let mut names: Vec<String> = names.into_iter().collect();
+    rng.shuffle(&mut names);
+    let mut tunnels: Vec<Vec<usize>> = vec![vec![]; n];
+    let connect = |a: usize, b: usize, tunnels: &mut Vec<Vec<usize>>| {
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
+        }
+    };
+    // mostly long corridors, like the real input
+    for i in 1..n {
+        let j = i - 1 - rng.below(i.min(3));
+        connect(i, j, &mut tunnels);
+    }
+    for _ in 0..n / 10 {
+        let (a, b) = (rng.below(n), rng.below(n));
+        connect(a, b, &mut tunnels);
+    }
+    let mut rates = vec![0; n];
+    let mut candidates: Vec<usize> = (0..n).filter(|&i| names[i] != "AA").collect();
+    rng.shuffle(&mut candidates);
+    for &i in candidates.iter().take((n / 4 + 1).min(15)) {
+        rates[i] = rng.range(1, 25);
+    }
+    let mut s = String::new();
+    for i in 0..n {
+        let to: Vec<&str> = tunnels[i].iter().map(|&t| names[t].as_str()).collect();
+        let (tunnel, lead, valve) = if to.len() == 1 {
+            ("tunnel", "leads", "valve")
+        } else {
+            ("tunnels", "lead", "valves")
+        };
+        writeln!(
+            s,
+            "Valve {} has flow rate={}; {} {} to {} {}",
+            names[i],
+            rates[i],
+            tunnel,
+            lead,
+            valve,
+            to.join(", ")
+        )
+        .unwrap();
+    }
+    s
+}
+
+fn day17(rng: &mut Rng, size: usize) -> String {
+    let mut s = rng.letters(b"<>", size.max(1));
+    s.push('\n');
+    s
+}
+
+// the solver's flood fill stays within -1..=22
+fn day18(rng: &mut Rng, size: usize) -> String {
+    let extent = ((size as f64).cbrt() * 1.5).clamp(2., 21.) as i64;
+    let volume = ((extent + 1) * (extent + 1) * (extent + 1)) as usize;
+    let mut cubes = BTreeSet::new();
+    while cubes.len() < size.min(volume / 2).max(1) {
+        let c = (
+            rng.range(0, extent),
+            rng.range(0, extent),
+            rng.range(0, extent),
+        );
+        cubes.insert(c);
+    }
+    let mut cubes: Vec<(i64, i64, i64)> = cubes.into_iter().collect();
+    rng.shuffle(&mut cubes);
+    let mut s = String::new();
+    for (x, y, z) in cubes {
+        writeln!(s, "{},{},{}", x, y, z).unwrap();
+    }
+    s
+}
+
+fn day19(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for id in 1..=size.max(3) {
+        writeln!(
+            s,
+            "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.",
+            id,
+            rng.range(2, 4),
+            rng.range(2, 4),
+            rng.range(2, 4),
+            rng.range(4, 20),
+            rng.range(2, 4),
+            rng.range(5, 20)
+        )
+        .unwrap();
+    }
+    s
+}
+
+// exactly one zero
+fn day20(rng: &mut Rng, size: usize) -> String {
+    let n = size.max(2);
+    let zero = rng.below(n);
+    let mut s = String::new();
+    for i in 0..n {
+        let v = if i == zero {
+            0
+        } else {
+            let v = rng.range(1, 10000);
+            if rng.chance(1, 2) {
+                v
+            } else {
+                -v
+            }
+        };
+        writeln!(s, "{}", v).unwrap();
+    }
+    s
+}
+
+// built top-down from the value every monkey must yell, so every division is exact
+// and both sides of root are equal: part 2 recovers the generated humn value
+fn day21(rng: &mut Rng, size: usize) -> String {
+    fn monkey(
+        rng: &mut Rng,
+        lines: &mut Vec<String>,
+        names: &mut BTreeSet<String>,
+        value: u64,
+        budget: usize,
+        human: bool,
+    ) -> String {
+        let name = if human && budget == 0 {
+            "humn".to_owned()
+        } else {
+            loop {
+                let name = rng.letters(LOWER, 4);
+                if name != "humn" && name != "root" && names.insert(name.clone()) {
+                    break name;
+                }
+            }
+        };
+        if budget == 0 {
+            lines.push(format!("{}: {}", name, value));
+            return name;
+        }
+        let (op, a, b) = loop {
+            match rng.below(4) {
+                0 if value >= 2 => {
+                    let a = rng.range(1, value as i64 - 1) as u64;
+                    break ('+', a, value - a);
+                }
+                1 => {
+                    let b = rng.range(1, 100) as u64;
+                    break ('-', value + b, b);
+                }
+                2 => {
+                    let divisors: Vec<u64> = (2..10).filter(|d| value.is_multiple_of(*d)).collect();
+                    if !divisors.is_empty() {
+                        let d = *rng.pick(&divisors);
+                        break ('*', value / d, d);
+                    }
+                }
+                3 if value < 1 << 40 => {
+                    let d = rng.range(2, 9) as u64;
+                    break ('/', value * d, d);
+                }
+                _ => (),
+            }
+        };
+        let left = rng.below(budget);
+        let human_left = rng.chance(1, 2);
+        let a = monkey(rng, lines, names, a, left, human && human_left);
+        let b = monkey(
+            rng,
+            lines,
+            names,
+            b,
+            budget - 1 - left,
+            human && !human_left,
+        );
+        let (a, b) = if rng.chance(1, 2) || op == '-' || op == '/' {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        lines.push(format!("{}: {} {} {}", name, a, op, b));
+        name
+    }
+
+    let budget = size.max(2) / 2;
+    let value = rng.range(1000, 1_000_000) as u64;
+    let mut lines = Vec::new();
+    let mut names = BTreeSet::new();
+    let a = monkey(rng, &mut lines, &mut names, value, budget, true);
+    let b = monkey(rng, &mut lines, &mut names, value, budget, false);
+    let (a, b) = if rng.chance(1, 2) { (a, b) } else { (b, a) };
+    lines.push(format!("root: {} + {}", a, b));
+    rng.shuffle(&mut lines);
+    let mut s = lines.join("\n");
+    s.push('\n');
+    s
+}
+
+// the solver's cube folding only knows the net of the real input, with 50x50 faces
+fn day22(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for y in 0..200 {
+        let (from, to) = match y {
+            0..=49 => (50, 150),
+            50..=99 => (50, 100),
+            100..=149 => (0, 100),
+            _ => (0, 50),
+        };
+        let row: String = (0..to)
+            .map(|x| match x {
+                x if x < from => ' ',
+                50 if y == 0 => '.',
+                _ if rng.chance(1, 12) => '#',
+                _ => '.',
+            })
+            .collect();
+        writeln!(s, "{}", row).unwrap();
+    }
+    s.push('\n');
+    for i in 0..size.max(1) {
+        if i > 0 {
+            s.push(*rng.pick(&['L', 'R']));
+        }
+        write!(s, "{}", rng.range(1, 50)).unwrap();
+    }
+    s.push('\n');
+    s
+}
+
+fn day23(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size.max(1) {
+        let row: String = (0..size.max(1))
+            .map(|_| if rng.chance(2, 5) { '#' } else { '.' })
+            .collect();
+        writeln!(s, "{}", row).unwrap();
+    }
+    s
+}
+
+// the solver precomputes 600 minutes of blizzards, so both dimensions divide 600
+fn day24(rng: &mut Rng, size: usize) -> String {
+    fn divisor_up_to(n: usize) -> usize {
+        (1..=n.clamp(3, 600)).rev().find(|d| 600 % d == 0).unwrap()
+    }
+    let (w, h) = (divisor_up_to(size), divisor_up_to(size / 5));
+    let mut s = format!("#.{}\n", "#".repeat(w));
+    for _ in 0..h {
+        let row: String = (0..w)
+            .map(|_| {
+                if rng.chance(1, 2) {
+                    *rng.pick(&['<', '>', '^', 'v'])
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(s, "#{}#", row).unwrap();
+    }
+    writeln!(s, "{}.#", "#".repeat(w)).unwrap();
+    s
+}
+
+fn day25(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size.max(1) {
+        let digits = rng.range(1, 20) as u32;
+        let mut n = rng.range(1, 5_i64.pow(digits - 1) * 3);
+        let mut snafu = Vec::new();
+        while n > 0 {
+            let (d, carry) = match n % 5 {
+                3 => ('=', 1),
+                4 => ('-', 1),
+                d => ((b'0' + d as u8) as char, 0),
+            };
+            snafu.push(d);
+            n = n / 5 + carry;
+        }
+        writeln!(s, "{}", snafu.iter().rev().collect::<String>()).unwrap();
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{context::Context, solver};
+
+    #[test]
+    fn test_reproducible() {
+        for day in 1..=25 {
+            let size = default_size(day);
+            assert_eq!(generate(day, 42, size), generate(day, 42, size));
+            assert_ne!(generate(day, 42, size), generate(day, 43, size));
+        }
+    }
+
+    #[test]
+    fn test_day10_size() {
+        for size in [1, 240, 241, 1000] {
+            let text = generate(10, 7, size);
+            let cycles: usize = text
+                .lines()
+                .map(|l| if l.starts_with("addx") { 2 } else { 1 })
+                .sum();
+            assert_eq!(cycles, size.div_ceil(240).max(1) * 240);
+            let input = TempInput::new(text).unwrap();
+            let letters = day10::part2(input.filename()).unwrap();
+            assert_eq!(letters.len(), 8);
+        }
+    }
+
+    #[test]
+    fn test_solvers_accept_generated_input() {
+        for s in solver::SOLVERS {
+            match (s.day, s.part) {
+                (19, 2) => continue, // too slow for a test
+                _ => (),
+            }
+            let size = match s.day {
+                15 | 16 | 19 => 3,
+                22 => default_size(22),
+                d => default_size(d) / 10,
+            };
+            let input = write_input(s.day, 2022, size).unwrap();
+            (s.solve)(input.filename(), &Context::new())
+                .unwrap_or_else(|e| panic!("day {} part {}: {}", s.day, s.part, e))
+                .unwrap();
+        }
+    }
+}
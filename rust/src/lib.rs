@@ -2,6 +2,15 @@
 
 mod util;
 
+#[cfg(test)]
+mod check;
+pub mod context;
+pub mod generate;
+pub mod memory;
+
+pub mod serve;
+pub mod solver;
+
 pub mod day1;
 pub mod day2;
 pub mod day3;
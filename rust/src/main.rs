@@ -0,0 +1,161 @@
+use std::{env, process, time::Duration, time::Instant};
+
+use aoc_2022::{
+    context::{Context, Outcome},
+    generate, memory, serve, solver,
+};
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOC: memory::Counting = memory::Counting;
+
+fn usage() -> ! {
+    eprintln!("usage: aoc run [<day> [<part>]] [--input <file> | --seed <seed> [--size <size>]]");
+    eprintln!("               [--timeout <secs>] [--progress] [--alloc]");
+    eprintln!("       aoc generate <day> [--seed <seed>] [--size <size>]");
+    eprintln!("       aoc serve [--port <port>] [--max-body <bytes>] [--timeout <secs>]");
+    eprintln!("                 [--max-connections <n>] [--max-solves <n>]");
+    process::exit(2);
+}
+
+fn parse_or_usage<T: std::str::FromStr>(s: Option<String>) -> T {
+    s.and_then(|s| s.parse().ok()).unwrap_or_else(|| usage())
+}
+
+fn run(mut args: impl Iterator<Item = String>) {
+    let mut numbers = Vec::new();
+    let mut input = None;
+    let mut seed = None;
+    let mut size = None;
+    let mut timeout = None;
+    let mut progress = false;
+    let mut alloc = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input = Some(args.next().unwrap_or_else(|| usage())),
+            "--seed" => seed = Some(parse_or_usage(args.next())),
+            "--size" => size = Some(parse_or_usage(args.next())),
+            "--timeout" => timeout = Some(Duration::from_secs(parse_or_usage(args.next()))),
+            "--progress" => progress = true,
+            "--alloc" => alloc = true,
+            _ => numbers.push(parse_or_usage::<u32>(Some(arg))),
+        }
+    }
+    if alloc && !cfg!(feature = "alloc-stats") {
+        eprintln!("aoc run: --alloc needs a build with `--features alloc-stats`");
+        process::exit(2);
+    }
+    let mut failed = false;
+    for s in solver::SOLVERS {
+        match numbers[..] {
+            [] => (),
+            [day] if s.day == day => (),
+            [day, part] if s.day == day && s.part == part => (),
+            [_] | [_, _] => continue,
+            _ => usage(),
+        }
+        let generated = seed.map(|seed| {
+            let size = size.unwrap_or_else(|| generate::default_size(s.day));
+            generate::write_input(s.day, seed, size).unwrap()
+        });
+        let filename = match (&generated, &input) {
+            (Some(generated), _) => generated.filename().to_owned(),
+            (None, Some(input)) => input.clone(),
+            (None, None) => solver::input_file(s.day),
+        };
+        let mut ctx = Context::new();
+        if let Some(timeout) = timeout {
+            ctx = ctx.with_timeout(timeout);
+        }
+        if progress {
+            ctx = ctx.with_progress(|p| match p.best {
+                None => eprint!("\r{} scanned", p.scanned),
+                Some(best) => eprint!("\r{} scanned, best so far {}", p.scanned, best),
+            });
+        }
+        let start = Instant::now();
+        let (outcome, stats) = memory::measure(|| (s.solve)(&filename, &ctx));
+        let elapsed = start.elapsed();
+        if progress {
+            eprintln!();
+        }
+        let answer = match outcome {
+            Ok(Outcome::Complete(answer)) => answer,
+            Ok(Outcome::Interrupted(best)) => {
+                format!("{} (timeout)", best.as_deref().unwrap_or("-"))
+            }
+            Err(e) => {
+                eprintln!("aoc run: day {} part {}: {}", s.day, s.part, e);
+                failed = true;
+                "(error)".to_owned()
+            }
+        };
+        print!(
+            "day {:2} part {}: {:>20} {:>12.3} ms",
+            s.day,
+            s.part,
+            answer,
+            elapsed.as_secs_f64() * 1000.
+        );
+        if alloc {
+            print!(
+                " {:>12} allocs {:>14} bytes {:>12} peak",
+                stats.allocations, stats.bytes, stats.peak
+            );
+        }
+        println!();
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn generate(mut args: impl Iterator<Item = String>) {
+    let day: u32 = parse_or_usage(args.next());
+    let mut seed = 0;
+    let mut size = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = parse_or_usage(args.next()),
+            "--size" => size = Some(parse_or_usage(args.next())),
+            _ => usage(),
+        }
+    }
+    if !(1..=25).contains(&day) {
+        usage();
+    }
+    let size = size.unwrap_or_else(|| generate::default_size(day));
+    print!("{}", generate::generate(day, seed, size));
+}
+
+fn serve(mut args: impl Iterator<Item = String>) {
+    let mut config = serve::Config::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => config.port = parse_or_usage(args.next()),
+            "--max-body" => config.max_body = parse_or_usage(args.next()),
+            "--timeout" => config.timeout = Duration::from_secs(parse_or_usage(args.next())),
+            "--max-connections" => config.max_connections = parse_or_usage(args.next()),
+            "--max-solves" => config.max_solves = parse_or_usage(args.next()),
+            _ => usage(),
+        }
+    }
+    let result = serve::bind(&config).and_then(|listener| {
+        eprintln!("listening on {}", listener.local_addr()?);
+        serve::run(listener, config)
+    });
+    if let Err(e) = result {
+        eprintln!("aoc serve: {}", e);
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("run") => run(args),
+        Some("generate") => generate(args),
+        Some("serve") => serve(args),
+        _ => usage(),
+    }
+}
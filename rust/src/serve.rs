@@ -0,0 +1,415 @@
+use std::{
+    any::Any,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    context::{Context, Outcome},
+    solver::{self, Solve},
+    util::{json_string, TempInput},
+};
+
+pub struct Config {
+    pub port: u16,
+    pub max_body: usize,
+    pub timeout: Duration,
+    pub max_connections: usize,
+    // solvers running at once, counting the ones whose request already timed out
+    pub max_solves: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            port: 2022,
+            max_body: 1 << 20,
+            timeout: Duration::from_secs(30),
+            max_connections: 64,
+            max_solves: thread::available_parallelism().map_or(4, |n| n.get()),
+        }
+    }
+}
+
+// counts what is in use, up to a maximum
+struct Slots {
+    used: AtomicUsize,
+    max: usize,
+}
+
+// gives its slot back when dropped
+struct Slot(&'static Slots);
+
+impl Slots {
+    fn new(max: usize) -> Self {
+        Slots {
+            used: AtomicUsize::new(0),
+            max,
+        }
+    }
+
+    fn take(&'static self) -> Option<Slot> {
+        self.used
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < self.max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Slot(self))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.used.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+struct Server {
+    config: Config,
+    connections: Slots,
+    solves: Slots,
+}
+
+const MAX_HEADER: usize = 8 << 10;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn error(status: u16, msg: &str) -> Self {
+        Response {
+            status,
+            body: format!("{{\"error\":{}}}", json_string(msg)),
+        }
+    }
+
+    fn write_to(&self, stream: &mut TcpStream) -> io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            500 => "Internal Server Error",
+            _ => "Service Unavailable",
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            self.body.len(),
+            self.body
+        )?;
+        stream.flush()
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "solver panicked".to_owned()
+    }
+}
+
+fn io_error(e: io::Error) -> Response {
+    match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            Response::error(408, "timed out reading request")
+        }
+        io::ErrorKind::UnexpectedEof => Response::error(400, "unexpected end of request"),
+        _ => Response::error(400, &e.to_string()),
+    }
+}
+
+fn read_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    header_len: &mut usize,
+) -> Result<(), Response> {
+    line.clear();
+    match reader.read_line(line).map_err(io_error)? {
+        0 => Err(Response::error(400, "unexpected end of request")),
+        n if *header_len + n > MAX_HEADER => Err(Response::error(431, "request header too large")),
+        n => {
+            *header_len += n;
+            Ok(())
+        }
+    }
+}
+
+// returns (method, path, body) or the response to send back
+fn read_request(
+    stream: &TcpStream,
+    max_body: usize,
+) -> Result<(String, String, Vec<u8>), Response> {
+    let mut reader = BufReader::new(stream.take((MAX_HEADER + max_body) as u64));
+    let mut header_len = 0;
+    let mut line = String::new();
+    read_line(&mut reader, &mut line, &mut header_len)?;
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.len() != 3 || !tokens[2].starts_with("HTTP/1.") {
+        return Err(Response::error(400, "malformed request line"));
+    }
+    let (method, path) = (tokens[0].to_owned(), tokens[1].to_owned());
+    let mut content_length = None;
+    loop {
+        read_line(&mut reader, &mut line, &mut header_len)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                match value.trim().parse::<usize>() {
+                    Ok(n) => content_length = Some(n),
+                    Err(_) => return Err(Response::error(400, "invalid Content-Length")),
+                }
+            }
+        }
+    }
+    let body = match (method.as_str(), content_length) {
+        ("POST", None) => return Err(Response::error(411, "Content-Length required")),
+        (_, Some(n)) if n > max_body => {
+            return Err(Response::error(
+                413,
+                &format!("request body exceeds {} bytes", max_body),
+            ))
+        }
+        (_, Some(n)) => {
+            let mut body = vec![0; n];
+            reader.read_exact(&mut body).map_err(io_error)?;
+            body
+        }
+        (_, None) => Vec::new(),
+    };
+    Ok((method, path, body))
+}
+
+// expects `/day/<day>/part/<part>`
+fn parse_path(path: &str) -> Option<(u32, u32)> {
+    let tokens: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match tokens[..] {
+        ["day", day, "part", part] => Some((day.parse().ok()?, part.parse().ok()?)),
+        _ => None,
+    }
+}
+
+fn result(
+    status: u16,
+    day: u32,
+    part: u32,
+    answer: Option<&str>,
+    time: Duration,
+    error: Option<&str>,
+) -> Response {
+    let or_null = |s: Option<&str>| s.map_or("null".to_owned(), json_string);
+    Response {
+        status,
+        body: format!(
+            "{{\"day\":{},\"part\":{},\"answer\":{},\"time_ms\":{:.3},\"error\":{}}}",
+            day,
+            part,
+            or_null(answer),
+            time.as_secs_f64() * 1000.,
+            or_null(error)
+        ),
+    }
+}
+
+fn solve(day: u32, part: u32, solve: Solve, input: Vec<u8>, server: &'static Server) -> Response {
+    let timeout = server.config.timeout;
+    // held by the solver thread until it is done, timed out request or not
+    let slot = match server.solves.take() {
+        Some(slot) => slot,
+        None => return Response::error(503, "too many puzzles being solved, try again later"),
+    };
+    let input = match TempInput::new(input) {
+        Ok(input) => input,
+        Err(e) => return Response::error(503, &e.to_string()),
+    };
+    let (tx, rx) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name(format!("day {} part {}", day, part))
+        .spawn(move || {
+            let start = Instant::now();
+            let ctx = Context::new().with_deadline(start + timeout);
+            let result =
+                panic::catch_unwind(panic::AssertUnwindSafe(|| solve(input.filename(), &ctx)));
+            let elapsed = start.elapsed();
+            drop(input);
+            drop(slot);
+            // receiver is gone when the request already timed out
+            let _ = tx.send((result, elapsed));
+        });
+    if let Err(e) = spawned {
+        return Response::error(503, &e.to_string());
+    }
+    // solvers without cancellation support are abandoned after the grace period
+    match rx.recv_timeout(timeout + GRACE_PERIOD) {
+        Ok((Ok(Ok(Outcome::Complete(answer))), elapsed)) => {
+            result(200, day, part, Some(&answer), elapsed, None)
+        }
+        Ok((Ok(Ok(Outcome::Interrupted(best))), elapsed)) => result(
+            503,
+            day,
+            part,
+            best.as_deref(),
+            elapsed,
+            Some("timed out, answer is the best result so far"),
+        ),
+        Ok((Ok(Err(e)), elapsed)) => result(422, day, part, None, elapsed, Some(&e)),
+        // the panic itself went to stderr
+        Ok((Err(payload), elapsed)) => result(
+            500,
+            day,
+            part,
+            None,
+            elapsed,
+            Some(&format!("solver panicked: {}", panic_message(payload))),
+        ),
+        Err(_) => result(503, day, part, None, timeout, Some("timed out")),
+    }
+}
+
+fn handle(mut stream: TcpStream, server: &'static Server) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let response = match read_request(&stream, server.config.max_body) {
+        Err(response) => response,
+        Ok((method, path, body)) => match parse_path(&path) {
+            None => Response::error(404, "expected /day/<day>/part/<part>"),
+            Some(_) if method != "POST" => Response::error(405, "expected POST"),
+            Some((day, part)) => match solver::find(day, part) {
+                None => Response::error(404, &format!("no solver for day {} part {}", day, part)),
+                Some(s) => solve(day, part, s.solve, body, server),
+            },
+        },
+    };
+    response.write_to(&mut stream)
+}
+
+pub fn bind(config: &Config) -> io::Result<TcpListener> {
+    TcpListener::bind(("127.0.0.1", config.port))
+}
+
+pub fn run(listener: TcpListener, config: Config) -> io::Result<()> {
+    let server: &'static Server = Box::leak(Box::new(Server {
+        connections: Slots::new(config.max_connections),
+        solves: Slots::new(config.max_solves),
+        config,
+    }));
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        let Some(slot) = server.connections.take() else {
+            // don't let a slow client hold up accepting
+            stream.set_write_timeout(Some(READ_TIMEOUT))?;
+            let _ = Response::error(503, "too many connections").write_to(&mut stream);
+            continue;
+        };
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, server) {
+                eprintln!("aoc serve: {}", e);
+            }
+            drop(slot);
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(addr: &str, path: &str, body: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serve() {
+        let config = Config {
+            port: 0,
+            max_body: 64,
+            timeout: Duration::from_secs(5),
+            ..Config::default()
+        };
+        let listener = bind(&config).unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || run(listener, config));
+
+        let response = post(&addr, "/day/6/part/1", "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"answer\":\"7\""));
+
+        // malformed input is the client's fault
+        let response = post(&addr, "/day/2/part/1", "A Q\n");
+        assert!(response.starts_with("HTTP/1.1 422"));
+        assert!(response.contains("\"answer\":null"));
+        assert!(response.contains("line 1: unknown letter Q"));
+
+        let response = post(&addr, "/day/5/part/1", "[A]\n 1 \n\nmove 2 from 1 to 3\n");
+        assert!(response.starts_with("HTTP/1.1 422"));
+        assert!(response.contains("move 1: "));
+
+        // an OCR failure is an error, not an answer
+        let response = post(&addr, "/day/10/part/2", "noop\n");
+        assert!(response.starts_with("HTTP/1.1 422"));
+        assert!(response.contains("unknown letter at position 0"));
+
+        let response = post(&addr, "/day/26/part/1", "");
+        assert!(response.starts_with("HTTP/1.1 404"));
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        write!(
+            stream,
+            "POST /day/1/part/1 HTTP/1.1\r\nContent-Length: 65\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413"));
+    }
+
+    #[test]
+    fn test_serve_busy() {
+        let config = Config {
+            port: 0,
+            max_solves: 0,
+            ..Config::default()
+        };
+        let listener = bind(&config).unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || run(listener, config));
+
+        let response = post(&addr, "/day/6/part/1", "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n");
+        assert!(response.starts_with("HTTP/1.1 503"));
+    }
+}
@@ -0,0 +1,102 @@
+use crate::context::{Context, Outcome};
+use crate::util::load;
+use crate::*;
+
+// `Err` when the input cannot be solved, with the reason
+pub type Solve = fn(&str, &Context) -> Result<Outcome<String>, String>;
+
+pub struct Solver {
+    pub day: u32,
+    pub part: u32,
+    pub solve: Solve,
+}
+
+macro_rules! solver {
+    // solvers that can be interrupted through the context
+    ($day:expr, $part:expr, with_context $f:expr) => {
+        Solver {
+            day: $day,
+            part: $part,
+            solve: |filename, ctx| Ok($f(filename, ctx).map(|v| v.to_string())),
+        }
+    };
+    // solvers returning a `Result`
+    ($day:expr, $part:expr, fallible $f:expr) => {
+        Solver {
+            day: $day,
+            part: $part,
+            solve: |filename, _| {
+                $f(filename)
+                    .map(|v| Outcome::Complete(v.to_string()))
+                    .map_err(|e| e.to_string())
+            },
+        }
+    };
+    ($day:expr, $part:expr, $f:expr) => {
+        Solver {
+            day: $day,
+            part: $part,
+            solve: |filename, _| Ok(Outcome::Complete($f(filename).to_string())),
+        }
+    };
+}
+
+pub const SOLVERS: &[Solver] = &[
+    solver!(1, 1, |f| day1::part1(load(f))),
+    solver!(1, 2, |f| day1::part2(load(f))),
+    solver!(2, 1, fallible day2::part1),
+    solver!(2, 2, fallible day2::part2),
+    solver!(3, 1, fallible day3::part1),
+    solver!(3, 2, fallible day3::part2),
+    solver!(4, 1, day4::part1),
+    solver!(4, 2, day4::part2),
+    solver!(5, 1, fallible day5::part1),
+    solver!(5, 2, fallible day5::part2),
+    solver!(6, 1, |f| day6::part1(&load::<String>(f)[0])),
+    solver!(6, 2, |f| day6::part2(&load::<String>(f)[0])),
+    solver!(7, 1, fallible day7::part1),
+    solver!(7, 2, fallible day7::part2),
+    solver!(8, 1, day8::part1),
+    solver!(8, 2, day8::part2),
+    solver!(9, 1, day9::part1),
+    solver!(9, 2, day9::part2),
+    solver!(10, 1, fallible day10::part1),
+    solver!(10, 2, fallible day10::part2),
+    solver!(11, 1, day11::part1),
+    solver!(11, 2, day11::part2),
+    solver!(12, 1, day12::part1),
+    solver!(12, 2, day12::part2),
+    solver!(13, 1, day13::part1),
+    solver!(13, 2, day13::part2),
+    solver!(14, 1, day14::part1),
+    solver!(14, 2, day14::part2),
+    solver!(15, 1, day15::part1),
+    solver!(15, 2, with_context day15::part2_with),
+    solver!(16, 1, with_context day16::part1_with),
+    solver!(16, 2, with_context day16::part2_with),
+    solver!(17, 1, day17::part1),
+    solver!(17, 2, day17::part2),
+    solver!(18, 1, day18::part1),
+    solver!(18, 2, day18::part2),
+    solver!(19, 1, with_context day19::part1_with),
+    solver!(19, 2, with_context day19::part2_with),
+    solver!(20, 1, day20::part1),
+    solver!(20, 2, day20::part2),
+    solver!(21, 1, day21::part1),
+    solver!(21, 2, day21::part2),
+    solver!(22, 1, day22::part1),
+    solver!(22, 2, day22::part2),
+    solver!(23, 1, day23::part1),
+    solver!(23, 2, day23::part2),
+    solver!(24, 1, day24::part1),
+    solver!(24, 2, day24::part2),
+    solver!(25, 1, day25::part1),
+];
+
+pub fn find(day: u32, part: u32) -> Option<&'static Solver> {
+    SOLVERS.iter().find(|s| s.day == day && s.part == part)
+}
+
+pub fn input_file(day: u32) -> String {
+    format!("data/day{}.txt", day)
+}
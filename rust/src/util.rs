@@ -1,7 +1,11 @@
+use std::env;
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::process;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub fn load<T>(filename: &str) -> Vec<T>
 where
@@ -15,3 +19,48 @@ where
         .map(|l| l.unwrap().parse().unwrap())
         .collect()
 }
+
+static TEMP_ID: AtomicUsize = AtomicUsize::new(0);
+
+// puzzle input written to a temporary file, removed again when dropped
+pub struct TempInput {
+    path: PathBuf,
+}
+
+impl TempInput {
+    pub fn new(contents: impl AsRef<[u8]>) -> io::Result<Self> {
+        let id = TEMP_ID.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("aoc-input-{}-{}.txt", process::id(), id));
+        fs::write(&path, contents)?;
+        Ok(TempInput { path })
+    }
+
+    pub fn filename(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+}
+
+impl Drop for TempInput {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// a JSON string literal, quotes included
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
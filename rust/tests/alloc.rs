@@ -0,0 +1,31 @@
+// Installs the counting allocator, so it runs on its own without the test harness
+// and nothing else allocates while it measures.
+use std::hint::black_box;
+
+use aoc_2022::memory::{measure, Counting, Stats};
+
+#[global_allocator]
+static ALLOC: Counting = Counting;
+
+fn main() {
+    let (len, stats) = measure(|| {
+        let big: Vec<u8> = black_box(Vec::with_capacity(1 << 20));
+        let boxes: Vec<Box<u64>> = black_box((0..100).map(Box::new).collect());
+        // grows once, from 4 to 8 items
+        let mut grown: Vec<u32> = Vec::with_capacity(4);
+        grown.extend([1, 2, 3, 4, 5]);
+        black_box(&grown);
+        big.capacity() + boxes.len() + grown.len()
+    });
+    assert_eq!(len, (1 << 20) + 100 + 5);
+    assert_eq!(
+        stats,
+        Stats {
+            // the big one, the boxes and their vector, both sizes of the grown one
+            allocations: 1 + 100 + 1 + 2,
+            bytes: (1 << 20) + 100 * 8 + 100 * 8 + 4 * 4 + 8 * 4,
+            // the grown vector's first block was freed when it moved
+            peak: (1 << 20) + 100 * 8 + 100 * 8 + 8 * 4,
+        }
+    );
+}